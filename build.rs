@@ -1,6 +1,14 @@
+#[cfg(feature = "tf2-cpp")]
 use std::{env, path::PathBuf};
 
 fn main() {
+    // The pure-Rust backend needs neither a C++ toolchain nor a ROS install.
+    #[cfg(feature = "tf2-cpp")]
+    build_tf2_wrapper();
+}
+
+#[cfg(feature = "tf2-cpp")]
+fn build_tf2_wrapper() {
     let mut b = cxx_build::bridge("src/ffi.rs");
     b.file("src/tf2_wrapper.cpp")
         .include("include")
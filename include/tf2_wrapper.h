@@ -19,6 +19,13 @@ struct Tf2TransformStamped;
 
 struct Tf2PointStamped;
 struct Tf2PoseStamped;
+struct Tf2Vector3Stamped;
+struct Tf2QuaternionStamped;
+struct Tf2PoseWithCovarianceStamped;
+struct Tf2TwistStamped;
+struct Tf2WrenchStamped;
+struct Tf2Point32;
+struct Tf2PolygonStamped;
 
 struct Tf2PointField;
 struct Tf2PointCloud2;
@@ -27,6 +34,7 @@ struct Tf2PointCloud2;
 enum class Tf2Errc : int32_t;
 struct Tf2Status;
 struct Tf2CanTransformResult;
+struct Tf2ParentResult;
 
 class BufferCoreWrapper {
 public:
@@ -56,6 +64,54 @@ public:
       const Tf2Time& time,
       Tf2TransformStamped& out_tf) const;
 
+  // Time-travel variants: source data at source_time is carried through
+  // fixed_frame (assumed static over time) into target_frame at target_time.
+  Tf2Status can_transform_full(
+      rust::Str target_frame,
+      const Tf2Time& target_time,
+      rust::Str source_frame,
+      const Tf2Time& source_time,
+      rust::Str fixed_frame,
+      Tf2CanTransformResult& out) const;
+
+  Tf2Status lookup_transform_full(
+      rust::Str target_frame,
+      const Tf2Time& target_time,
+      rust::Str source_frame,
+      const Tf2Time& source_time,
+      rust::Str fixed_frame,
+      Tf2TransformStamped& out_tf) const;
+
+  // Frame graph introspection.
+  Tf2Status frame_names(rust::Vec<rust::String>& out) const;
+
+  bool frame_exists(rust::Str frame_id) const;
+
+  Tf2Status get_parent(
+      rust::Str frame_id,
+      const Tf2Time& time,
+      Tf2ParentResult& out) const;
+
+  // tf2's _getLatestCommonTime; (0,0) if the frames are only connected
+  // through static transforms.
+  Tf2Status latest_common_time(
+      rust::Str target_frame,
+      rust::Str source_frame,
+      Tf2Time& out) const;
+
+  // tf2's _chainAsVector: frames walked from source_frame to target_frame.
+  Tf2Status chain_as_vector(
+      rust::Str target_frame,
+      const Tf2Time& target_time,
+      rust::Str source_frame,
+      const Tf2Time& source_time,
+      rust::Str fixed_frame,
+      rust::Vec<rust::String>& out) const;
+
+  rust::String all_frames_as_yaml() const;
+
+  rust::String all_frames_as_string() const;
+
 private:
   mutable tf2::BufferCore buffer_;
 };
@@ -73,6 +129,41 @@ Tf2Status do_transform_pose_stamped(
     const Tf2TransformStamped& tf,
     Tf2PoseStamped& out);
 
+Tf2Status do_transform_vector3_stamped(
+    const Tf2Vector3Stamped& input,
+    const Tf2TransformStamped& tf,
+    Tf2Vector3Stamped& out);
+
+Tf2Status do_transform_quaternion_stamped(
+    const Tf2QuaternionStamped& input,
+    const Tf2TransformStamped& tf,
+    Tf2QuaternionStamped& out);
+
+Tf2Status do_transform_transform_stamped(
+    const Tf2TransformStamped& input,
+    const Tf2TransformStamped& tf,
+    Tf2TransformStamped& out);
+
+Tf2Status do_transform_pose_with_covariance_stamped(
+    const Tf2PoseWithCovarianceStamped& input,
+    const Tf2TransformStamped& tf,
+    Tf2PoseWithCovarianceStamped& out);
+
+Tf2Status do_transform_twist_stamped(
+    const Tf2TwistStamped& input,
+    const Tf2TransformStamped& tf,
+    Tf2TwistStamped& out);
+
+Tf2Status do_transform_wrench_stamped(
+    const Tf2WrenchStamped& input,
+    const Tf2TransformStamped& tf,
+    Tf2WrenchStamped& out);
+
+Tf2Status do_transform_polygon_stamped(
+    const Tf2PolygonStamped& input,
+    const Tf2TransformStamped& tf,
+    Tf2PolygonStamped& out);
+
 Tf2Status do_transform_pointcloud2(
     const Tf2PointCloud2& input,
     const Tf2TransformStamped& tf,
@@ -0,0 +1,14 @@
+//! Storage behind [`BufferCore`](crate::BufferCore): tf2's C++ `BufferCore`
+//! through cxx with the `tf2-cpp` feature (the default), the pure-Rust
+//! [`FrameTree`](crate::native::FrameTree) otherwise. Both expose the same
+//! primitives; everything else on `BufferCore` is built on top of them.
+
+#[cfg(feature = "tf2-cpp")]
+mod tf2_cpp;
+#[cfg(feature = "tf2-cpp")]
+pub(crate) use self::tf2_cpp::Backend;
+
+#[cfg(not(feature = "tf2-cpp"))]
+mod native;
+#[cfg(not(feature = "tf2-cpp"))]
+pub(crate) use self::native::Backend;
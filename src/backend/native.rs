@@ -0,0 +1,145 @@
+use crate::buffer::TransformAvailability;
+use crate::error::Tf2Error;
+use crate::native::FrameTree;
+use crate::time::LookupTime;
+use crate::transform_stamped::TransformStamped;
+use std::sync::{Arc, PoisonError, RwLock, RwLockReadGuard, RwLockWriteGuard};
+use std::time::Duration;
+
+#[derive(Clone)]
+pub(crate) struct Backend {
+    tree: Arc<RwLock<FrameTree>>,
+}
+
+impl Backend {
+    pub fn new(cache_time: Duration) -> Self {
+        Self {
+            tree: Arc::new(RwLock::new(FrameTree::new(cache_time))),
+        }
+    }
+
+    fn read(&self) -> RwLockReadGuard<'_, FrameTree> {
+        self.tree.read().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    fn write(&self) -> RwLockWriteGuard<'_, FrameTree> {
+        self.tree.write().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    pub fn clear(&self) {
+        self.write().clear();
+    }
+
+    pub fn set_transform(
+        &self,
+        tf: &TransformStamped,
+        authority: &str,
+        is_static: bool,
+    ) -> Result<(), Tf2Error> {
+        self.write().set_transform(tf, authority, is_static)
+    }
+
+    pub fn check_transform(
+        &self,
+        target_frame: &str,
+        source_frame: &str,
+        when: LookupTime,
+    ) -> Result<TransformAvailability, Tf2Error> {
+        Ok(self
+            .read()
+            .check_transform(target_frame, source_frame, when))
+    }
+
+    pub fn lookup_transform(
+        &self,
+        target_frame: &str,
+        source_frame: &str,
+        when: LookupTime,
+    ) -> Result<TransformStamped, Tf2Error> {
+        self.read()
+            .lookup_transform(target_frame, source_frame, when)
+    }
+
+    pub fn lookup_transform_full(
+        &self,
+        target_frame: &str,
+        target_time: LookupTime,
+        source_frame: &str,
+        source_time: LookupTime,
+        fixed_frame: &str,
+    ) -> Result<TransformStamped, Tf2Error> {
+        self.read().lookup_transform_full(
+            target_frame,
+            target_time,
+            source_frame,
+            source_time,
+            fixed_frame,
+        )
+    }
+
+    pub fn check_transform_full(
+        &self,
+        target_frame: &str,
+        target_time: LookupTime,
+        source_frame: &str,
+        source_time: LookupTime,
+        fixed_frame: &str,
+    ) -> Result<TransformAvailability, Tf2Error> {
+        Ok(self.read().check_transform_full(
+            target_frame,
+            target_time,
+            source_frame,
+            source_time,
+            fixed_frame,
+        ))
+    }
+
+    pub fn frame_names(&self) -> Result<Vec<String>, Tf2Error> {
+        Ok(self.read().frame_names())
+    }
+
+    pub fn frame_exists(&self, frame_id: &str) -> bool {
+        self.read().frame_exists(frame_id)
+    }
+
+    pub fn parent_frame(
+        &self,
+        frame_id: &str,
+        when: LookupTime,
+    ) -> Result<Option<String>, Tf2Error> {
+        Ok(self.read().parent_frame(frame_id, when))
+    }
+
+    pub fn latest_common_time(
+        &self,
+        target_frame: &str,
+        source_frame: &str,
+    ) -> Result<LookupTime, Tf2Error> {
+        self.read().latest_common_time(target_frame, source_frame)
+    }
+
+    pub fn chain_as_vector(
+        &self,
+        target_frame: &str,
+        target_time: LookupTime,
+        source_frame: &str,
+        source_time: LookupTime,
+        fixed_frame: &str,
+    ) -> Result<Vec<String>, Tf2Error> {
+        self.read().chain_as_vector(
+            target_frame,
+            target_time,
+            source_frame,
+            source_time,
+            fixed_frame,
+        )
+    }
+
+    pub fn all_frames_as_yaml(&self) -> String {
+        self.read().all_frames_as_yaml()
+    }
+
+    pub fn all_frames_as_string(&self) -> String {
+        self.read().all_frames_as_string()
+    }
+}
@@ -0,0 +1,199 @@
+use crate::buffer::TransformAvailability;
+use crate::error::Tf2Error;
+use crate::ffi::ffi::{self, BufferCoreWrapper};
+use crate::ffi_utils::{call_bool, call_out};
+use crate::time::LookupTime;
+use crate::transform_stamped::TransformStamped;
+use std::time::Duration;
+
+unsafe impl Send for BufferCoreWrapper {}
+unsafe impl Sync for BufferCoreWrapper {}
+
+#[derive(Clone)]
+pub(crate) struct Backend {
+    inner: cxx::SharedPtr<BufferCoreWrapper>,
+}
+
+impl Backend {
+    pub fn new(cache_time: Duration) -> Self {
+        Self {
+            inner: ffi::new_buffer_core(cache_time.as_nanos() as u64),
+        }
+    }
+
+    fn wrapper(&self) -> &BufferCoreWrapper {
+        self.inner.as_ref().expect("BufferCoreWrapper is null")
+    }
+
+    pub fn clear(&self) {
+        self.wrapper().clear();
+    }
+
+    pub fn set_transform(
+        &self,
+        tf: &TransformStamped,
+        authority: &str,
+        is_static: bool,
+    ) -> Result<(), Tf2Error> {
+        let ok = call_bool(|out_ok| {
+            self.wrapper()
+                .set_transform(&tf.to_ffi(), authority, is_static, out_ok)
+        })?;
+
+        if ok {
+            Ok(())
+        } else {
+            Err(Tf2Error::InvalidArgument(
+                "buffer_set_transform rejected transform".to_string(),
+            ))
+        }
+    }
+
+    pub fn check_transform(
+        &self,
+        target_frame: &str,
+        source_frame: &str,
+        when: LookupTime,
+    ) -> Result<TransformAvailability, Tf2Error> {
+        let t = ffi::Tf2Time::from(when);
+        let out = call_out(|out| {
+            self.wrapper()
+                .can_transform(target_frame, source_frame, &t, out)
+        })?;
+
+        Ok(availability(out))
+    }
+
+    pub fn lookup_transform(
+        &self,
+        target_frame: &str,
+        source_frame: &str,
+        when: LookupTime,
+    ) -> Result<TransformStamped, Tf2Error> {
+        let t = ffi::Tf2Time::from(when);
+        let ffi_tf = call_out(|out| {
+            self.wrapper()
+                .lookup_transform(target_frame, source_frame, &t, out)
+        })?;
+
+        Ok(TransformStamped::from_ffi(ffi_tf))
+    }
+
+    pub fn lookup_transform_full(
+        &self,
+        target_frame: &str,
+        target_time: LookupTime,
+        source_frame: &str,
+        source_time: LookupTime,
+        fixed_frame: &str,
+    ) -> Result<TransformStamped, Tf2Error> {
+        let target_t = ffi::Tf2Time::from(target_time);
+        let source_t = ffi::Tf2Time::from(source_time);
+        let ffi_tf = call_out(|out| {
+            self.wrapper().lookup_transform_full(
+                target_frame,
+                &target_t,
+                source_frame,
+                &source_t,
+                fixed_frame,
+                out,
+            )
+        })?;
+
+        Ok(TransformStamped::from_ffi(ffi_tf))
+    }
+
+    pub fn check_transform_full(
+        &self,
+        target_frame: &str,
+        target_time: LookupTime,
+        source_frame: &str,
+        source_time: LookupTime,
+        fixed_frame: &str,
+    ) -> Result<TransformAvailability, Tf2Error> {
+        let target_t = ffi::Tf2Time::from(target_time);
+        let source_t = ffi::Tf2Time::from(source_time);
+        let out = call_out(|out| {
+            self.wrapper().can_transform_full(
+                target_frame,
+                &target_t,
+                source_frame,
+                &source_t,
+                fixed_frame,
+                out,
+            )
+        })?;
+
+        Ok(availability(out))
+    }
+
+    pub fn frame_names(&self) -> Result<Vec<String>, Tf2Error> {
+        call_out(|out| self.wrapper().frame_names(out))
+    }
+
+    pub fn frame_exists(&self, frame_id: &str) -> bool {
+        self.wrapper().frame_exists(frame_id)
+    }
+
+    pub fn parent_frame(
+        &self,
+        frame_id: &str,
+        when: LookupTime,
+    ) -> Result<Option<String>, Tf2Error> {
+        let t = ffi::Tf2Time::from(when);
+        let out = call_out(|out| self.wrapper().get_parent(frame_id, &t, out))?;
+        Ok(out.has_parent.then_some(out.parent_frame))
+    }
+
+    pub fn latest_common_time(
+        &self,
+        target_frame: &str,
+        source_frame: &str,
+    ) -> Result<LookupTime, Tf2Error> {
+        let t = call_out(|out| {
+            self.wrapper()
+                .latest_common_time(target_frame, source_frame, out)
+        })?;
+        Ok(t.into())
+    }
+
+    pub fn chain_as_vector(
+        &self,
+        target_frame: &str,
+        target_time: LookupTime,
+        source_frame: &str,
+        source_time: LookupTime,
+        fixed_frame: &str,
+    ) -> Result<Vec<String>, Tf2Error> {
+        let target_t = ffi::Tf2Time::from(target_time);
+        let source_t = ffi::Tf2Time::from(source_time);
+        call_out(|out| {
+            self.wrapper().chain_as_vector(
+                target_frame,
+                &target_t,
+                source_frame,
+                &source_t,
+                fixed_frame,
+                out,
+            )
+        })
+    }
+
+    pub fn all_frames_as_yaml(&self) -> String {
+        self.wrapper().all_frames_as_yaml()
+    }
+
+    pub fn all_frames_as_string(&self) -> String {
+        self.wrapper().all_frames_as_string()
+    }
+}
+
+fn availability(out: ffi::Tf2CanTransformResult) -> TransformAvailability {
+    if out.available {
+        TransformAvailability::Available
+    } else {
+        TransformAvailability::Unavailable {
+            diagnostic: (!out.diagnostic.is_empty()).then_some(out.diagnostic),
+        }
+    }
+}
@@ -1,17 +1,113 @@
-use rclrs::{IntoPrimitiveOptions, Publisher};
+use crate::error::{BroadcastError, InvalidTransform};
+use crate::transform_stamped::TransformStamped;
+use rclrs::{IntoPrimitiveOptions, Publisher, QOS_PROFILE_DEFAULT, QoSProfile, log_error};
+use std::collections::BTreeMap;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex, MutexGuard, PoisonError};
+use std::time::Duration;
 use tf2_msgs::msg::TFMessage;
 
+/// Configuration for [`TransformBroadcaster::new_with_options`] and
+/// [`StaticTransformBroadcaster::new_with_options`]. Start from
+/// [`BroadcasterOptions::tf`] or [`BroadcasterOptions::tf_static`], which
+/// match the respective `new`.
+#[derive(Clone, Debug)]
+pub struct BroadcasterOptions {
+    pub topic: String,
+    pub qos: QoSProfile,
+    /// Prepended to the parent and child frame of every transform sent, like
+    /// robot_state_publisher's `frame_prefix` (e.g. `"robot1/"`).
+    pub frame_prefix: String,
+    /// Rescale rotations whose norm is within
+    /// [`QUATERNION_RESCALE_TOLERANCE`](crate::QUATERNION_RESCALE_TOLERANCE)
+    /// of one before validating instead of rejecting the transform.
+    pub normalize_quaternions: bool,
+}
+
+impl BroadcasterOptions {
+    /// `/tf`, reliable with a depth of 100.
+    pub fn tf() -> Self {
+        Self {
+            topic: "/tf".to_string(),
+            qos: QOS_PROFILE_DEFAULT.keep_last(100).reliable(),
+            frame_prefix: String::new(),
+            normalize_quaternions: false,
+        }
+    }
+
+    /// `/tf_static`, reliable and transient local with a depth of 1.
+    pub fn tf_static() -> Self {
+        Self {
+            topic: "/tf_static".to_string(),
+            qos: QOS_PROFILE_DEFAULT
+                .keep_last(1)
+                .reliable()
+                .transient_local(),
+            frame_prefix: String::new(),
+            normalize_quaternions: false,
+        }
+    }
+
+    pub fn topic(mut self, topic: impl Into<String>) -> Self {
+        self.topic = topic.into();
+        self
+    }
+
+    pub fn qos(mut self, qos: QoSProfile) -> Self {
+        self.qos = qos;
+        self
+    }
+
+    pub fn frame_prefix(mut self, prefix: impl Into<String>) -> Self {
+        self.frame_prefix = prefix.into();
+        self
+    }
+
+    pub fn normalize_quaternions(mut self, normalize: bool) -> Self {
+        self.normalize_quaternions = normalize;
+        self
+    }
+}
+
 #[derive(Clone)]
 struct TfBroadcasterInner {
     pub_: Publisher<TFMessage>,
+    frame_prefix: String,
+    normalize_quaternions: bool,
 }
 
 impl TfBroadcasterInner {
-    fn new(pub_: Publisher<TFMessage>) -> Self {
-        Self { pub_ }
+    fn new(node: &rclrs::Node, options: BroadcasterOptions) -> Result<Self, rclrs::RclrsError> {
+        let pub_ = node.create_publisher(options.topic.as_str().qos(options.qos))?;
+        Ok(Self {
+            pub_,
+            frame_prefix: options.frame_prefix,
+            normalize_quaternions: options.normalize_quaternions,
+        })
+    }
+
+    /// Prefixes, optionally normalizes, and validates a transform to send.
+    fn prepare<T>(&self, tf: T) -> Result<geometry_msgs::msg::TransformStamped, InvalidTransform>
+    where
+        T: Into<geometry_msgs::msg::TransformStamped>,
+    {
+        prepare_transform(&self.frame_prefix, self.normalize_quaternions, tf.into())
+    }
+
+    /// Prepares every transform, failing on the first invalid one so that
+    /// nothing is published from a partly invalid batch.
+    fn prepare_all<I, T>(
+        &self,
+        tfs: I,
+    ) -> Result<Vec<geometry_msgs::msg::TransformStamped>, InvalidTransform>
+    where
+        I: IntoIterator<Item = T>,
+        T: Into<geometry_msgs::msg::TransformStamped>,
+    {
+        tfs.into_iter().map(|tf| self.prepare(tf)).collect()
     }
 
-    fn send_transform<T>(&self, tf: T) -> Result<(), rclrs::RclrsError>
+    fn send_transform<T>(&self, tf: T) -> Result<(), BroadcastError>
     where
         T: Into<geometry_msgs::msg::TransformStamped>,
     {
@@ -19,18 +115,72 @@ impl TfBroadcasterInner {
         self.send_transforms(std::iter::once(tf))
     }
 
-    fn send_transforms<I, T>(&self, tfs: I) -> Result<(), rclrs::RclrsError>
+    fn send_transforms<I, T>(&self, tfs: I) -> Result<(), BroadcastError>
     where
         I: IntoIterator<Item = T>,
         T: Into<geometry_msgs::msg::TransformStamped>,
     {
         let msg = TFMessage {
-            transforms: tfs.into_iter().map(Into::into).collect(),
+            transforms: self.prepare_all(tfs)?,
         };
+        Ok(self.publish(msg)?)
+    }
+
+    fn publish(&self, msg: TFMessage) -> Result<(), rclrs::RclrsError> {
         self.pub_.publish(msg)
     }
 }
 
+fn apply_frame_prefix(
+    prefix: &str,
+    mut tf: geometry_msgs::msg::TransformStamped,
+) -> geometry_msgs::msg::TransformStamped {
+    if !prefix.is_empty() {
+        tf.header.frame_id.insert_str(0, prefix);
+        tf.child_frame_id.insert_str(0, prefix);
+    }
+    tf
+}
+
+fn prepare_transform(
+    prefix: &str,
+    normalize_quaternions: bool,
+    msg: geometry_msgs::msg::TransformStamped,
+) -> Result<geometry_msgs::msg::TransformStamped, InvalidTransform> {
+    let mut tf = TransformStamped::from(&apply_frame_prefix(prefix, msg));
+    if normalize_quaternions {
+        tf.normalize_rotation();
+    }
+    tf.validate()?;
+    Ok(tf.into())
+}
+
+/// Latest static transform per child frame, as tf2_ros keeps them.
+#[derive(Default)]
+struct StaticTransforms {
+    by_child: BTreeMap<String, geometry_msgs::msg::TransformStamped>,
+}
+
+impl StaticTransforms {
+    fn insert(&mut self, tf: geometry_msgs::msg::TransformStamped) {
+        self.by_child.insert(tf.child_frame_id.clone(), tf);
+    }
+
+    fn remove(&mut self, child_frame: &str) -> bool {
+        self.by_child.remove(child_frame).is_some()
+    }
+
+    fn clear(&mut self) {
+        self.by_child.clear();
+    }
+
+    fn to_message(&self) -> TFMessage {
+        TFMessage {
+            transforms: self.by_child.values().cloned().collect(),
+        }
+    }
+}
+
 #[derive(Clone)]
 pub struct TransformBroadcaster {
     inner: TfBroadcasterInner,
@@ -38,20 +188,26 @@ pub struct TransformBroadcaster {
 
 impl TransformBroadcaster {
     pub fn new(node: &rclrs::Node) -> Result<Self, rclrs::RclrsError> {
-        let pub_ = node.create_publisher("/tf".keep_last(100).reliable())?;
+        Self::new_with_options(node, BroadcasterOptions::tf())
+    }
+
+    pub fn new_with_options(
+        node: &rclrs::Node,
+        options: BroadcasterOptions,
+    ) -> Result<Self, rclrs::RclrsError> {
         Ok(Self {
-            inner: TfBroadcasterInner::new(pub_),
+            inner: TfBroadcasterInner::new(node, options)?,
         })
     }
 
-    pub fn send_transform<T>(&self, tf: T) -> Result<(), rclrs::RclrsError>
+    pub fn send_transform<T>(&self, tf: T) -> Result<(), BroadcastError>
     where
         T: Into<geometry_msgs::msg::TransformStamped>,
     {
         self.inner.send_transform(tf)
     }
 
-    pub fn send_transforms<I, T>(&self, tfs: I) -> Result<(), rclrs::RclrsError>
+    pub fn send_transforms<I, T>(&self, tfs: I) -> Result<(), BroadcastError>
     where
         I: IntoIterator<Item = T>,
         T: Into<geometry_msgs::msg::TransformStamped>,
@@ -60,31 +216,282 @@ impl TransformBroadcaster {
     }
 }
 
+/// Publishes on the static topic of its [`BroadcasterOptions`]
+/// (`/tf_static` by default). Like tf2_ros, every transform sent so far is
+/// kept (the newest per child frame) and the whole set is republished on each
+/// change, so late joiners receive all of them from the single latched
+/// message. Clones share the set.
 #[derive(Clone)]
 pub struct StaticTransformBroadcaster {
     inner: TfBroadcasterInner,
+    transforms: Arc<Mutex<StaticTransforms>>,
 }
 
 impl StaticTransformBroadcaster {
     pub fn new(node: &rclrs::Node) -> Result<Self, rclrs::RclrsError> {
-        let pub_ = node.create_publisher("/tf_static".keep_last(1).reliable().transient_local())?;
+        Self::new_with_options(node, BroadcasterOptions::tf_static())
+    }
+
+    pub fn new_with_options(
+        node: &rclrs::Node,
+        options: BroadcasterOptions,
+    ) -> Result<Self, rclrs::RclrsError> {
         Ok(Self {
-            inner: TfBroadcasterInner::new(pub_),
+            inner: TfBroadcasterInner::new(node, options)?,
+            transforms: Arc::default(),
         })
     }
 
-    pub fn send_transform<T>(&self, tf: T) -> Result<(), rclrs::RclrsError>
+    fn transforms(&self) -> MutexGuard<'_, StaticTransforms> {
+        self.transforms
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+    }
+
+    pub fn send_transform<T>(&self, tf: T) -> Result<(), BroadcastError>
     where
         T: Into<geometry_msgs::msg::TransformStamped>,
     {
-        self.inner.send_transform(tf)
+        self.send_transforms(std::iter::once(tf))
     }
 
-    pub fn send_transforms<I, T>(&self, tfs: I) -> Result<(), rclrs::RclrsError>
+    pub fn send_transforms<I, T>(&self, tfs: I) -> Result<(), BroadcastError>
     where
         I: IntoIterator<Item = T>,
         T: Into<geometry_msgs::msg::TransformStamped>,
     {
-        self.inner.send_transforms(tfs)
+        // Publish under the lock so concurrent senders cannot latch a stale set.
+        let prepared = self.inner.prepare_all(tfs)?;
+        let mut transforms = self.transforms();
+        for tf in prepared {
+            transforms.insert(tf);
+        }
+        Ok(self.inner.publish(transforms.to_message())?)
+    }
+
+    /// Stops advertising the transform to `child_frame` (without the frame
+    /// prefix) and republishes the rest. Returns `false` if there was none.
+    /// Nodes that already received it keep it, as tf2 never expires static
+    /// transforms.
+    pub fn remove_transform(&self, child_frame: &str) -> Result<bool, rclrs::RclrsError> {
+        let child_frame = format!("{}{child_frame}", self.inner.frame_prefix);
+        let mut transforms = self.transforms();
+        if !transforms.remove(&child_frame) {
+            return Ok(false);
+        }
+        self.inner.publish(transforms.to_message())?;
+        Ok(true)
+    }
+
+    /// Drops every transform and latches an empty message.
+    pub fn clear(&self) -> Result<(), rclrs::RclrsError> {
+        let mut transforms = self.transforms();
+        transforms.clear();
+        self.inner.publish(transforms.to_message())
+    }
+}
+
+type TransformProducer = dyn FnMut() -> Vec<TransformStamped> + Send;
+
+/// Publishes the transforms returned by a producer closure on `/tf` at a
+/// fixed period, stamped with the node clock. Shared state (e.g. odometry)
+/// can be read by capturing an `Arc<Mutex<_>>` in the closure.
+///
+/// The timer stops when this is dropped.
+pub struct PeriodicTransformBroadcaster {
+    logger: rclrs::Logger,
+    node: rclrs::Node,
+    broadcaster: TransformBroadcaster,
+    producer: Arc<Mutex<Box<TransformProducer>>>,
+    paused: Arc<AtomicBool>,
+    period: Duration,
+    _timer: rclrs::Timer,
+}
+
+impl PeriodicTransformBroadcaster {
+    pub fn new<F>(
+        node: &rclrs::Node,
+        period: Duration,
+        producer: F,
+    ) -> Result<Self, rclrs::RclrsError>
+    where
+        F: FnMut() -> Vec<TransformStamped> + Send + 'static,
+    {
+        let broadcaster = TransformBroadcaster::new(node)?;
+        Self::with_broadcaster(node, broadcaster, period, producer)
+    }
+
+    /// Like [`PeriodicTransformBroadcaster::new`], publishing through an
+    /// existing `broadcaster`.
+    pub fn with_broadcaster<F>(
+        node: &rclrs::Node,
+        broadcaster: TransformBroadcaster,
+        period: Duration,
+        producer: F,
+    ) -> Result<Self, rclrs::RclrsError>
+    where
+        F: FnMut() -> Vec<TransformStamped> + Send + 'static,
+    {
+        let producer: Arc<Mutex<Box<TransformProducer>>> = Arc::new(Mutex::new(Box::new(producer)));
+        let paused = Arc::new(AtomicBool::new(false));
+        let logger = node.logger().clone();
+        let timer = start_timer(node, &logger, &broadcaster, &producer, &paused, period)?;
+
+        Ok(Self {
+            logger,
+            node: node.clone(),
+            broadcaster,
+            producer,
+            paused,
+            period,
+            _timer: timer,
+        })
+    }
+
+    /// Stops publishing until [`resume`](Self::resume); the timer keeps running.
+    pub fn pause(&self) {
+        self.paused.store(true, Ordering::Release);
+    }
+
+    pub fn resume(&self) {
+        self.paused.store(false, Ordering::Release);
+    }
+
+    pub fn is_paused(&self) -> bool {
+        self.paused.load(Ordering::Acquire)
+    }
+
+    pub fn period(&self) -> Duration {
+        self.period
+    }
+
+    /// Switches to a new publishing period, replacing the timer.
+    pub fn set_period(&mut self, period: Duration) -> Result<(), rclrs::RclrsError> {
+        self._timer = start_timer(
+            &self.node,
+            &self.logger,
+            &self.broadcaster,
+            &self.producer,
+            &self.paused,
+            period,
+        )?;
+        self.period = period;
+        Ok(())
+    }
+}
+
+fn start_timer(
+    node: &rclrs::Node,
+    logger: &rclrs::Logger,
+    broadcaster: &TransformBroadcaster,
+    producer: &Arc<Mutex<Box<TransformProducer>>>,
+    paused: &Arc<AtomicBool>,
+    period: Duration,
+) -> Result<rclrs::Timer, rclrs::RclrsError> {
+    let clock = node.get_clock();
+    let logger = logger.clone();
+    let broadcaster = broadcaster.clone();
+    let producer = producer.clone();
+    let paused = paused.clone();
+
+    node.create_timer_repeating(period, move || {
+        if paused.load(Ordering::Acquire) {
+            return;
+        }
+        let mut tfs = (producer.lock().unwrap_or_else(PoisonError::into_inner))();
+        stamp_all(&mut tfs, clock.now().nsec);
+        if let Err(e) = broadcaster.send_transforms(tfs) {
+            log_error!(&logger, "Failed to publish periodic transforms: {}", e);
+        }
+    })
+}
+
+fn stamp_all(tfs: &mut [TransformStamped], now_ns: i64) {
+    let sec = now_ns.div_euclid(1_000_000_000) as i32;
+    let nanosec = now_ns.rem_euclid(1_000_000_000) as u32;
+    for tf in tfs {
+        tf.stamp_sec = sec;
+        tf.stamp_nanosec = nanosec;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn static_tf(parent: &str, child: &str, x: f64) -> geometry_msgs::msg::TransformStamped {
+        let mut tf = geometry_msgs::msg::TransformStamped::default();
+        tf.header.frame_id = parent.to_string();
+        tf.child_frame_id = child.to_string();
+        tf.transform.translation.x = x;
+        tf.transform.rotation.w = 1.0;
+        tf
+    }
+
+    #[test]
+    fn static_transforms_keep_newest_per_child() {
+        let mut set = StaticTransforms::default();
+        set.insert(static_tf("base_link", "laser", 1.0));
+        set.insert(static_tf("base_link", "camera", 2.0));
+        set.insert(static_tf("base_link", "laser", 3.0));
+
+        let msg = set.to_message();
+        assert_eq!(msg.transforms.len(), 2);
+        let laser = msg
+            .transforms
+            .iter()
+            .find(|t| t.child_frame_id == "laser")
+            .unwrap();
+        assert_eq!(laser.transform.translation.x, 3.0);
+
+        assert!(set.remove("camera"));
+        assert!(!set.remove("camera"));
+        assert_eq!(set.to_message().transforms.len(), 1);
+
+        set.clear();
+        assert!(set.to_message().transforms.is_empty());
+    }
+
+    #[test]
+    fn frame_prefix_applies_to_both_frames() {
+        let tf = apply_frame_prefix("robot1/", static_tf("base_link", "laser", 1.0));
+        assert_eq!(tf.header.frame_id, "robot1/base_link");
+        assert_eq!(tf.child_frame_id, "robot1/laser");
+
+        let tf = apply_frame_prefix("", static_tf("base_link", "laser", 1.0));
+        assert_eq!(tf.header.frame_id, "base_link");
+    }
+
+    #[test]
+    fn prepare_transform_validates_after_prefixing() {
+        let mut tf = static_tf("base_link", "laser", 1.0);
+        tf.transform.rotation.w = 2.0;
+        assert!(matches!(
+            prepare_transform("robot1/", false, tf.clone()),
+            Err(InvalidTransform::NonUnitQuaternion { .. })
+        ));
+
+        let tf = prepare_transform("robot1/", true, tf).unwrap();
+        assert_eq!(tf.child_frame_id, "robot1/laser");
+        assert_eq!(tf.transform.rotation.w, 1.0);
+
+        assert_eq!(
+            prepare_transform("", false, static_tf("laser", "laser", 0.0)),
+            Err(InvalidTransform::SelfTransform("laser".to_string()))
+        );
+    }
+
+    #[test]
+    fn stamp_all_uses_clock_time() {
+        let mut tfs = vec![TransformStamped {
+            stamp_sec: 0,
+            stamp_nanosec: 0,
+            parent_frame: "odom".to_string(),
+            child_frame: "base_link".to_string(),
+            translation: [0.0; 3],
+            rotation: [0.0, 0.0, 0.0, 1.0],
+        }];
+        stamp_all(&mut tfs, 12_250_000_000);
+        assert_eq!((tfs[0].stamp_sec, tfs[0].stamp_nanosec), (12, 250_000_000));
     }
 }
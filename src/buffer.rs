@@ -1,52 +1,75 @@
+use crate::backend::Backend;
 use crate::error::Tf2Error;
-use crate::ffi::ffi::{self, BufferCoreWrapper};
-use crate::ffi_utils::{call_bool, call_out};
+use crate::frame_graph::{FrameGraph, FrameInfo, parse_frames_yaml};
+use crate::math;
+use crate::notify::TransformNotifier;
 use crate::time::{LookupTime, TimeSpec};
 use crate::transform::Transformable;
 use crate::transform_stamped::TransformStamped;
-
-
-unsafe impl Send for BufferCoreWrapper {}
-unsafe impl Sync for BufferCoreWrapper {}
+use crate::velocity_stamped::VelocityStamped;
+use std::collections::HashMap;
+use std::collections::hash_map::Entry;
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::task::{Context, Poll};
+use std::time::{Duration, Instant};
 
 #[derive(Clone)]
 pub struct BufferCore {
-    inner: cxx::SharedPtr<BufferCoreWrapper>,
+    inner: Backend,
+    notifier: Arc<TransformNotifier>,
+    normalize_quaternions: Arc<AtomicBool>,
 }
 
 impl BufferCore {
     pub fn new(cache_time_ns: std::time::Duration) -> Self {
         Self {
-            inner: ffi::new_buffer_core(cache_time_ns.as_nanos() as u64),
+            inner: Backend::new(cache_time_ns),
+            notifier: Arc::new(TransformNotifier::default()),
+            normalize_quaternions: Arc::new(AtomicBool::new(false)),
         }
     }
 
-    fn wrapper(&self) -> &BufferCoreWrapper {
-        self.inner.as_ref().expect("BufferCoreWrapper is null")
-    }
-
     pub fn clear(&mut self) {
-        self.wrapper().clear();
+        self.inner.clear();
     }
 
+    /// Inserts a transform after checking it with
+    /// [`TransformStamped::validate`].
     pub fn set_transform(
         &mut self,
         tf: &TransformStamped,
         authority: &str,
         is_static: bool,
     ) -> Result<(), Tf2Error> {
-        let ok = call_bool(|out_ok| {
-            self.wrapper()
-                .set_transform(&tf.to_ffi(), authority, is_static, out_ok)
-        })?;
-
-        if ok {
-            Ok(())
+        let normalized;
+        let tf = if self.normalizes_quaternions() {
+            let mut copy = tf.clone();
+            copy.normalize_rotation();
+            normalized = copy;
+            &normalized
         } else {
-            Err(Tf2Error::InvalidArgument(
-                "buffer_set_transform rejected transform".to_string(),
-            ))
-        }
+            tf
+        };
+        tf.validate()?;
+        self.inner.set_transform(tf, authority, is_static)?;
+        self.notifier.notify();
+        Ok(())
+    }
+
+    /// Opt in to rescaling rotations whose norm is within
+    /// [`QUATERNION_RESCALE_TOLERANCE`](crate::QUATERNION_RESCALE_TOLERANCE)
+    /// of one in `set_transform` instead of rejecting them. Rotations further
+    /// off are still rejected. Shared by all clones of this buffer.
+    pub fn set_normalize_quaternions(&self, normalize: bool) {
+        self.normalize_quaternions
+            .store(normalize, Ordering::Relaxed);
+    }
+
+    pub fn normalizes_quaternions(&self) -> bool {
+        self.normalize_quaternions.load(Ordering::Relaxed)
     }
 
     pub fn check_transform(
@@ -55,19 +78,8 @@ impl BufferCore {
         source_frame: &str,
         when: LookupTime,
     ) -> Result<TransformAvailability, Tf2Error> {
-        let t = ffi::Tf2Time::from(when);
-        let out = call_out(|out| {
-            self.wrapper()
-                .can_transform(target_frame, source_frame, &t, out)
-        })?;
-
-        Ok(if out.available {
-            TransformAvailability::Available
-        } else {
-            TransformAvailability::Unavailable {
-                diagnostic: (!out.diagnostic.is_empty()).then_some(out.diagnostic),
-            }
-        })
+        self.inner
+            .check_transform(target_frame, source_frame, when)
     }
 
     pub fn can_transform(
@@ -88,13 +100,141 @@ impl BufferCore {
         source_frame: &str,
         when: LookupTime,
     ) -> Result<TransformStamped, Tf2Error> {
-        let t = ffi::Tf2Time::from(when);
-        let ffi_tf = call_out(|out| {
-            self.wrapper()
-                .lookup_transform(target_frame, source_frame, &t, out)
-        })?;
+        self.inner
+            .lookup_transform(target_frame, source_frame, when)
+    }
+
+    /// Advanced "time travel" lookup: the transform that takes data stamped
+    /// `source_time` in `source_frame` to `target_frame` at `target_time`.
+    ///
+    /// `fixed_frame` must not move over the interval (typically `odom` or
+    /// `map`); tf2 goes `source -> fixed` at `source_time`, then
+    /// `fixed -> target` at `target_time`.
+    pub fn lookup_transform_full(
+        &self,
+        target_frame: &str,
+        target_time: LookupTime,
+        source_frame: &str,
+        source_time: LookupTime,
+        fixed_frame: &str,
+    ) -> Result<TransformStamped, Tf2Error> {
+        self.inner.lookup_transform_full(
+            target_frame,
+            target_time,
+            source_frame,
+            source_time,
+            fixed_frame,
+        )
+    }
 
-        Ok(TransformStamped::from_ffi(ffi_tf))
+    pub fn check_transform_full(
+        &self,
+        target_frame: &str,
+        target_time: LookupTime,
+        source_frame: &str,
+        source_time: LookupTime,
+        fixed_frame: &str,
+    ) -> Result<TransformAvailability, Tf2Error> {
+        self.inner.check_transform_full(
+            target_frame,
+            target_time,
+            source_frame,
+            source_time,
+            fixed_frame,
+        )
+    }
+
+    pub fn can_transform_full(
+        &self,
+        target_frame: &str,
+        target_time: LookupTime,
+        source_frame: &str,
+        source_time: LookupTime,
+        fixed_frame: &str,
+    ) -> Result<bool, Tf2Error> {
+        Ok(self
+            .check_transform_full(target_frame, target_time, source_frame, source_time, fixed_frame)?
+            .is_available())
+    }
+
+    /// Like [`BufferCore::lookup_transform`], but waits up to `timeout` for the
+    /// transform to become available instead of failing right away.
+    ///
+    /// The calling thread sleeps until `set_transform` inserts new data (for
+    /// example from a `TransformListener`) and then retries. Errors that new
+    /// data cannot fix are returned immediately; running out of time yields
+    /// [`Tf2Error::Timeout`]. `Duration::MAX` waits forever.
+    pub fn lookup_transform_timeout(
+        &self,
+        target_frame: &str,
+        source_frame: &str,
+        when: LookupTime,
+        timeout: Duration,
+    ) -> Result<TransformStamped, Tf2Error> {
+        // `None` when the timeout is too large to represent, e.g. `Duration::MAX`.
+        let deadline = Instant::now().checked_add(timeout);
+        loop {
+            let seen = self.notifier.generation();
+            match self.lookup_transform(target_frame, source_frame, when) {
+                Err(e) if e.is_retryable() => {
+                    if !self.notifier.wait_past(seen, deadline) {
+                        return Err(Tf2Error::Timeout {
+                            target_frame: target_frame.to_string(),
+                            source_frame: source_frame.to_string(),
+                            timeout,
+                            message: format!(
+                                "timed out after {timeout:?} waiting for transform {target_frame} <- {source_frame}: {e}"
+                            ),
+                        });
+                    }
+                }
+                res => return res,
+            }
+        }
+    }
+
+    /// Like [`BufferCore::can_transform`], but waits up to `timeout` for the
+    /// transform to become available. Returns `Ok(false)` once `timeout`
+    /// elapses.
+    pub fn can_transform_timeout(
+        &self,
+        target_frame: &str,
+        source_frame: &str,
+        when: LookupTime,
+        timeout: Duration,
+    ) -> Result<bool, Tf2Error> {
+        let deadline = Instant::now().checked_add(timeout);
+        loop {
+            let seen = self.notifier.generation();
+            if self.can_transform(target_frame, source_frame, when)? {
+                return Ok(true);
+            }
+            if !self.notifier.wait_past(seen, deadline) {
+                return Ok(false);
+            }
+        }
+    }
+
+    /// Returns a future that resolves once `target_frame <- source_frame` can be
+    /// looked up at `when`, typically after a `TransformListener` sharing this
+    /// buffer ingests the missing data.
+    ///
+    /// The future never blocks the executor: it is woken by `set_transform`.
+    /// Errors that new data cannot fix resolve the future immediately.
+    /// Dropping the future cancels the wait.
+    pub fn wait_for_transform(
+        &self,
+        target_frame: &str,
+        source_frame: &str,
+        when: LookupTime,
+    ) -> impl Future<Output = Result<TransformStamped, Tf2Error>> + Send + 'static {
+        WaitForTransform {
+            buffer: self.clone(),
+            target_frame: target_frame.to_string(),
+            source_frame: source_frame.to_string(),
+            when,
+            waiter: None,
+        }
     }
 
     pub fn transform<T: Transformable>(
@@ -108,6 +248,266 @@ impl BufferCore {
         msg.apply_transform(&tf)
     }
 
+    /// [`transform`](BufferCore::transform) without copying `msg`, for large
+    /// messages such as `PointCloud2`.
+    pub fn transform_in_place<T: Transformable>(
+        &self,
+        msg: &mut T,
+        target_frame: &str,
+        time: TimeSpec,
+    ) -> Result<(), Tf2Error> {
+        let when = time.resolve(msg);
+        let tf = self.lookup_transform(target_frame, msg.frame_id(), when)?;
+        msg.apply_transform_in_place(&tf)
+    }
+
+    /// [`transform`](BufferCore::transform) over a batch. Elements sharing a
+    /// frame and (resolved) time reuse a single lookup. Stops at the first
+    /// failure.
+    pub fn transform_many<T: Transformable>(
+        &self,
+        msgs: &[T],
+        target_frame: &str,
+        time: TimeSpec,
+    ) -> Result<Vec<T>, Tf2Error> {
+        let mut lookups: HashMap<(&str, LookupTime), TransformStamped> = HashMap::new();
+        msgs.iter()
+            .map(|msg| {
+                let key = (msg.frame_id(), time.resolve(msg));
+                let tf = match lookups.entry(key) {
+                    Entry::Occupied(entry) => entry.into_mut(),
+                    Entry::Vacant(entry) => {
+                        entry.insert(self.lookup_transform(target_frame, key.0, key.1)?)
+                    }
+                };
+                msg.apply_transform(tf)
+            })
+            .collect()
+    }
+
+    /// Names of every frame the buffer has seen, roots included.
+    pub fn frame_names(&self) -> Result<Vec<String>, Tf2Error> {
+        self.inner.frame_names()
+    }
+
+    pub fn frame_exists(&self, frame_id: &str) -> bool {
+        self.inner.frame_exists(frame_id)
+    }
+
+    /// Parent of `frame_id` at `when`, or `None` for root and unknown frames.
+    pub fn parent_frame(&self, frame_id: &str, when: LookupTime) -> Result<Option<String>, Tf2Error> {
+        self.inner.parent_frame(frame_id, when)
+    }
+
+    /// Typed snapshot of the frame tree: every frame with its parent, the
+    /// authority that published the edge and when it was last updated.
+    ///
+    /// The edges all come from a single `allFramesAsYAML` dump, so they are
+    /// consistent with each other even while transforms keep arriving.
+    pub fn frame_graph(&self) -> Result<FrameGraph, Tf2Error> {
+        // Frames are never forgotten, so listing them after the dump covers
+        // every frame it mentions.
+        let mut edges = parse_frames_yaml(&self.all_frames_as_yaml());
+
+        let frames = self
+            .frame_names()?
+            .into_iter()
+            .map(|frame_id| {
+                let edge = edges.remove(&frame_id).unwrap_or_default();
+                FrameInfo {
+                    frame_id,
+                    parent_frame: edge.parent,
+                    authority: edge.authority,
+                    last_update: edge.most_recent_transform,
+                    rate: edge.rate,
+                }
+            })
+            .collect();
+
+        Ok(FrameGraph { frames })
+    }
+
+    /// Latest time at which `target_frame <- source_frame` can be looked up,
+    /// i.e. the concrete stamp a [`LookupTime::Latest`] lookup between the two
+    /// resolves to (tf2's `_getLatestCommonTime`).
+    ///
+    /// Frames connected only through static transforms have no stamp, and
+    /// yield [`LookupTime::Latest`].
+    pub fn latest_common_time(
+        &self,
+        target_frame: &str,
+        source_frame: &str,
+    ) -> Result<LookupTime, Tf2Error> {
+        self.inner.latest_common_time(target_frame, source_frame)
+    }
+
+    /// Velocity of `tracking_frame` relative to `observation_frame` around
+    /// `time`, estimated by differencing two lookups `averaging_interval`
+    /// apart (tf2's `lookupVelocity`).
+    ///
+    /// The result is expressed in `reference_frame` and measured at
+    /// `reference_point` (given in `reference_frame`, assumed rigidly attached
+    /// to `tracking_frame`). The window is centered on `time` but clamped to the
+    /// newest data, so `LookupTime::Latest` averages over the last
+    /// `averaging_interval`.
+    pub fn lookup_velocity(
+        &self,
+        tracking_frame: &str,
+        observation_frame: &str,
+        reference_frame: &str,
+        reference_point: [f64; 3],
+        time: LookupTime,
+        averaging_interval: Duration,
+    ) -> Result<VelocityStamped, Tf2Error> {
+        let interval = averaging_interval.as_nanos() as i64;
+        if interval <= 0 {
+            return Err(Tf2Error::InvalidArgument(
+                "lookup_velocity needs a positive averaging interval".to_string(),
+            ));
+        }
+
+        let latest = self.latest_common_time(observation_frame, tracking_frame)?;
+        let (stamp_sec, stamp_nanosec) = match (time, latest) {
+            (LookupTime::Time { sec, nanosec }, _) | (_, LookupTime::Time { sec, nanosec }) => {
+                (sec, nanosec)
+            }
+            (LookupTime::Latest, LookupTime::Latest) => (0, 0),
+        };
+
+        // Frames only connected by static transforms cannot move.
+        let Some(latest_ns) = latest.as_nanos() else {
+            return Ok(VelocityStamped {
+                stamp_sec,
+                stamp_nanosec,
+                body_frame: tracking_frame.to_string(),
+                reference_frame: reference_frame.to_string(),
+                linear: [0.0; 3],
+                angular: [0.0; 3],
+            });
+        };
+        let target_ns = time.as_nanos().unwrap_or(latest_ns);
+
+        // Same window as tf2: centered on the target, clamped to the newest
+        // data and starting no earlier than tf2's 0.00001 s, since zero would
+        // mean "latest".
+        let end_ns = (target_ns + interval / 2).min(latest_ns);
+        let start_ns = (10_000 + interval).max(end_ns) - interval;
+        let dt = (end_ns - start_ns) as f64 * 1e-9;
+
+        let start = self.lookup_transform(
+            observation_frame,
+            tracking_frame,
+            LookupTime::from_nanos(start_ns),
+        )?;
+        let end = self.lookup_transform(
+            observation_frame,
+            tracking_frame,
+            LookupTime::from_nanos(end_ns),
+        )?;
+
+        // Angular velocity: relative rotation over the window, as an axis in
+        // the observation frame.
+        let delta = math::quat_mul(math::quat_conjugate(start.rotation), end.rotation);
+        let (axis, angle) = math::quat_to_axis_angle(delta);
+        let angular_obs = math::scale(math::quat_rotate(start.rotation, axis), angle / dt);
+        let linear_obs = math::scale(math::sub(end.translation, start.translation), 1.0 / dt);
+
+        let target = LookupTime::from_nanos(target_ns);
+        let reference_from_observation =
+            self.lookup_transform(reference_frame, observation_frame, target)?;
+        let reference_from_tracking = self.lookup_transform(reference_frame, tracking_frame, target)?;
+
+        let angular = math::quat_rotate(reference_from_observation.rotation, angular_obs);
+        let origin_linear = math::quat_rotate(reference_from_observation.rotation, linear_obs);
+        // Move the measurement from the tracking frame origin to the reference point.
+        let lever = math::sub(reference_point, reference_from_tracking.translation);
+        let linear = math::add(origin_linear, math::cross(angular, lever));
+
+        Ok(VelocityStamped {
+            stamp_sec,
+            stamp_nanosec,
+            body_frame: tracking_frame.to_string(),
+            reference_frame: reference_frame.to_string(),
+            linear,
+            angular,
+        })
+    }
+
+    /// Frames tf2 walks to get from `source_frame` to `target_frame` at
+    /// `when`, starting at `source_frame` and ending at `target_frame`
+    /// (tf2's `_chainAsVector`, with `target_frame` as the fixed frame).
+    /// A frame has an empty chain to itself.
+    ///
+    /// Fails when the two frames cannot be connected at `when`.
+    pub fn frame_chain(
+        &self,
+        target_frame: &str,
+        source_frame: &str,
+        when: LookupTime,
+    ) -> Result<Vec<String>, Tf2Error> {
+        self.inner
+            .chain_as_vector(target_frame, when, source_frame, when, target_frame)
+    }
+
+    /// Like [`BufferCore::frame_chain`], but returns the transform of every
+    /// edge along the chain as it is stored in the tree (`parent <- child`),
+    /// all taken at the same time.
+    pub fn frame_chain_transforms(
+        &self,
+        target_frame: &str,
+        source_frame: &str,
+        when: LookupTime,
+    ) -> Result<Vec<TransformStamped>, Tf2Error> {
+        // Pin `Latest` down first so that every edge is taken at the same stamp.
+        let at = match when {
+            LookupTime::Latest => self.latest_common_time(target_frame, source_frame)?,
+            stamp => stamp,
+        };
+        let chain = self.frame_chain(target_frame, source_frame, at)?;
+
+        chain
+            .windows(2)
+            .map(|hop| {
+                let (a, b) = (&hop[0], &hop[1]);
+                if self.parent_frame(a, at)?.as_deref() == Some(b.as_str()) {
+                    self.lookup_transform(b, a, at)
+                } else {
+                    self.lookup_transform(a, b, at)
+                }
+            })
+            .collect()
+    }
+
+    /// tf2's YAML dump of the frame tree (`allFramesAsYAML`).
+    pub fn all_frames_as_yaml(&self) -> String {
+        self.inner.all_frames_as_yaml()
+    }
+
+    /// tf2's human readable dump of the frame tree (`allFramesAsString`).
+    pub fn all_frames_as_string(&self) -> String {
+        self.inner.all_frames_as_string()
+    }
+
+    /// Re-expresses `msg` (stamped at its header time) in `target_frame` at
+    /// `target_time`, going through `fixed_frame`. See
+    /// [`BufferCore::lookup_transform_full`].
+    pub fn transform_full<T: Transformable>(
+        &self,
+        msg: &T,
+        target_frame: &str,
+        target_time: TimeSpec,
+        fixed_frame: &str,
+    ) -> Result<T, Tf2Error> {
+        let tf = self.lookup_transform_full(
+            target_frame,
+            target_time.resolve(msg),
+            msg.frame_id(),
+            LookupTime::from_msg(msg),
+            fixed_frame,
+        )?;
+        msg.apply_transform(&tf)
+    }
+
     pub fn ingest_tf_message(
         &mut self,
         msg: tf2_msgs::msg::TFMessage,
@@ -124,6 +524,51 @@ impl BufferCore {
     }
 }
 
+struct WaitForTransform {
+    buffer: BufferCore,
+    target_frame: String,
+    source_frame: String,
+    when: LookupTime,
+    waiter: Option<u64>,
+}
+
+impl Future for WaitForTransform {
+    type Output = Result<TransformStamped, Tf2Error>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let this = self.get_mut();
+        let notifier = &this.buffer.notifier;
+        loop {
+            let seen = notifier.generation();
+            match this
+                .buffer
+                .lookup_transform(&this.target_frame, &this.source_frame, this.when)
+            {
+                Err(e) if e.is_retryable() => {
+                    if notifier.register_waker(seen, &mut this.waiter, cx.waker()) {
+                        return Poll::Pending;
+                    }
+                }
+                res => {
+                    if let Some(id) = this.waiter.take() {
+                        notifier.remove_waker(id);
+                    }
+                    return Poll::Ready(res);
+                }
+            }
+        }
+    }
+}
+
+impl Drop for WaitForTransform {
+    fn drop(&mut self) {
+        if let Some(id) = self.waiter.take() {
+            self.buffer.notifier.remove_waker(id);
+        }
+    }
+}
+
+/// The concrete time a lookup resolved to; purely static chains keep `Latest`.
 #[derive(Clone, Debug, PartialEq, Eq)]
 pub enum TransformAvailability {
     Available,
@@ -143,3 +588,433 @@ impl TransformAvailability {
     }
 }
 
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::InvalidTransform;
+
+    fn make_tf(parent_frame: &str, child_frame: &str, stamp: (i32, u32)) -> TransformStamped {
+        TransformStamped {
+            stamp_sec: stamp.0,
+            stamp_nanosec: stamp.1,
+            parent_frame: parent_frame.to_string(),
+            child_frame: child_frame.to_string(),
+            translation: [1.0, 0.0, 0.0],
+            rotation: [0.0, 0.0, 0.0, 1.0],
+        }
+    }
+
+    #[test]
+    fn lookup_transform_timeout_wakes_on_set_transform() {
+        let buffer = BufferCore::new(Duration::from_secs(10));
+
+        let mut writer = buffer.clone();
+        let handle = std::thread::spawn(move || {
+            std::thread::sleep(Duration::from_millis(50));
+            writer
+                .set_transform(&make_tf("map", "base_link", (1, 0)), "test", false)
+                .expect("insert should succeed");
+        });
+
+        let tf = buffer
+            .lookup_transform_timeout("map", "base_link", LookupTime::Latest, Duration::from_secs(5))
+            .expect("lookup should succeed once the transform arrives");
+        assert_eq!(tf.parent_frame, "map");
+        assert_eq!(tf.child_frame, "base_link");
+
+        handle.join().unwrap();
+    }
+
+    #[test]
+    fn timeouts_accept_duration_max() {
+        let buffer = BufferCore::new(Duration::from_secs(10));
+
+        let mut writer = buffer.clone();
+        let handle = std::thread::spawn(move || {
+            std::thread::sleep(Duration::from_millis(50));
+            writer
+                .set_transform(&make_tf("map", "base_link", (1, 0)), "test", false)
+                .expect("insert should succeed");
+        });
+
+        assert!(
+            buffer
+                .can_transform_timeout("map", "base_link", LookupTime::Latest, Duration::MAX)
+                .unwrap()
+        );
+        let tf = buffer
+            .lookup_transform_timeout("map", "base_link", LookupTime::Latest, Duration::MAX)
+            .expect("lookup should succeed once the transform arrives");
+        assert_eq!(tf.child_frame, "base_link");
+
+        handle.join().unwrap();
+    }
+
+    #[test]
+    fn lookup_transform_timeout_reports_timeout() {
+        let buffer = BufferCore::new(Duration::from_secs(10));
+
+        let res = buffer.lookup_transform_timeout(
+            "map",
+            "base_link",
+            LookupTime::Latest,
+            Duration::from_millis(20),
+        );
+        assert!(
+            matches!(res, Err(Tf2Error::Timeout { .. })),
+            "expected a timeout, got: {res:?}"
+        );
+
+        let available = buffer
+            .can_transform_timeout("map", "base_link", LookupTime::Latest, Duration::from_millis(20))
+            .expect("probe should not fail");
+        assert!(!available);
+    }
+
+    #[test]
+    fn lookup_transform_full_goes_through_fixed_frame() {
+        let mut buffer = BufferCore::new(Duration::from_secs(10));
+
+        // base_link drives from x=0 at t=1 to x=5 at t=2; the camera is rigidly mounted.
+        let mut odom_base = make_tf("odom", "base_link", (1, 0));
+        odom_base.translation = [0.0, 0.0, 0.0];
+        buffer.set_transform(&odom_base, "test", false).unwrap();
+        odom_base.stamp_sec = 2;
+        odom_base.translation = [5.0, 0.0, 0.0];
+        buffer.set_transform(&odom_base, "test", false).unwrap();
+        buffer
+            .set_transform(&make_tf("base_link", "camera", (0, 0)), "test", true)
+            .unwrap();
+
+        // A detection seen by the camera at t=1, expressed in base_link at t=2.
+        let tf = buffer
+            .lookup_transform_full(
+                "base_link",
+                LookupTime::Time { sec: 2, nanosec: 0 },
+                "camera",
+                LookupTime::Time { sec: 1, nanosec: 0 },
+                "odom",
+            )
+            .expect("time travel lookup should succeed");
+
+        // camera origin at t=1 sits at odom x=1, i.e. 4m behind base_link at t=2.
+        assert!((tf.translation[0] + 4.0).abs() < 1e-9, "{tf:?}");
+        assert!(
+            buffer
+                .can_transform_full(
+                    "base_link",
+                    LookupTime::Time { sec: 2, nanosec: 0 },
+                    "camera",
+                    LookupTime::Time { sec: 1, nanosec: 0 },
+                    "odom",
+                )
+                .unwrap()
+        );
+    }
+
+    #[test]
+    fn set_transform_validates_and_optionally_normalizes() {
+        let mut buffer = BufferCore::new(Duration::from_secs(10));
+
+        let res = buffer.set_transform(&make_tf("/map", "base_link", (1, 0)), "test", false);
+        assert!(matches!(
+            res,
+            Err(Tf2Error::InvalidTransform(InvalidTransform::LeadingSlash(_)))
+        ));
+
+        let mut tf = make_tf("map", "base_link", (1, 0));
+        tf.rotation = [0.0, 0.0, 0.0, 1.05];
+        let res = buffer.set_transform(&tf, "test", false);
+        assert!(matches!(
+            res,
+            Err(Tf2Error::InvalidTransform(
+                InvalidTransform::NonUnitQuaternion { .. }
+            ))
+        ));
+
+        buffer.clone().set_normalize_quaternions(true);
+        assert!(buffer.normalizes_quaternions());
+        let mut far = tf.clone();
+        far.rotation = [0.0, 0.0, 0.0, 1.5];
+        let res = buffer.set_transform(&far, "test", false);
+        assert!(matches!(
+            res,
+            Err(Tf2Error::InvalidTransform(
+                InvalidTransform::NonUnitQuaternion { .. }
+            ))
+        ));
+        buffer.set_transform(&tf, "test", false).unwrap();
+        let out = buffer
+            .lookup_transform("map", "base_link", LookupTime::Latest)
+            .unwrap();
+        assert!((out.rotation[3] - 1.0).abs() < 1e-12);
+    }
+
+    #[test]
+    fn frame_graph_reports_parents_and_authorities() {
+        let mut buffer = BufferCore::new(Duration::from_secs(10));
+        buffer
+            .set_transform(&make_tf("odom", "base_link", (4, 5_000)), "odometry", false)
+            .unwrap();
+        buffer
+            .set_transform(&make_tf("base_link", "camera", (0, 0)), "urdf", true)
+            .unwrap();
+
+        assert!(buffer.frame_exists("camera"));
+        assert!(!buffer.frame_exists("lidar"));
+        assert_eq!(
+            buffer.parent_frame("camera", LookupTime::Latest).unwrap().as_deref(),
+            Some("base_link")
+        );
+
+        let graph = buffer.frame_graph().unwrap();
+        assert_eq!(graph.parent("base_link"), Some("odom"));
+        assert_eq!(
+            graph.roots().map(|f| f.frame_id.as_str()).collect::<Vec<_>>(),
+            vec!["odom"]
+        );
+
+        let base = graph.get("base_link").unwrap();
+        assert_eq!(base.authority.as_deref(), Some("odometry"));
+        assert_eq!(base.last_update, Some((4, 5_000)));
+
+        let camera = graph.get("camera").unwrap();
+        assert_eq!(camera.authority.as_deref(), Some("urdf"));
+        assert_eq!(camera.parent_frame.as_deref(), Some("base_link"));
+        assert_eq!(camera.last_update, None);
+    }
+
+    #[test]
+    fn frame_chain_follows_tf2_walk() {
+        let mut buffer = BufferCore::new(Duration::from_secs(10));
+        for (parent, child) in [
+            ("map", "odom"),
+            ("odom", "base_link"),
+            ("base_link", "arm"),
+            ("arm", "gripper"),
+            ("base_link", "camera"),
+            ("world", "island"),
+        ] {
+            buffer
+                .set_transform(&make_tf(parent, child, (0, 0)), "test", true)
+                .unwrap();
+        }
+
+        let up = buffer.frame_chain("map", "arm", LookupTime::Latest).unwrap();
+        assert_eq!(up, vec!["arm", "base_link", "odom", "map"]);
+
+        let down = buffer
+            .frame_chain("gripper", "odom", LookupTime::Latest)
+            .unwrap();
+        assert_eq!(down, vec!["odom", "base_link", "arm", "gripper"]);
+
+        let edges = buffer
+            .frame_chain_transforms("gripper", "odom", LookupTime::Latest)
+            .unwrap();
+        let pairs: Vec<(&str, &str)> = edges
+            .iter()
+            .map(|tf| (tf.parent_frame.as_str(), tf.child_frame.as_str()))
+            .collect();
+        assert_eq!(
+            pairs,
+            vec![("odom", "base_link"), ("base_link", "arm"), ("arm", "gripper")]
+        );
+        assert!(matches!(
+            buffer.frame_chain("map", "island", LookupTime::Latest),
+            Err(Tf2Error::Connectivity { .. })
+        ));
+    }
+
+    #[test]
+    fn latest_common_time_is_oldest_of_the_newest_stamps() {
+        let mut buffer = BufferCore::new(Duration::from_secs(10));
+        for sec in 1..=3 {
+            buffer
+                .set_transform(&make_tf("odom", "base_link", (sec, 0)), "test", false)
+                .unwrap();
+        }
+        for sec in 1..=2 {
+            buffer
+                .set_transform(&make_tf("base_link", "lidar", (sec, 500)), "test", false)
+                .unwrap();
+        }
+        buffer
+            .set_transform(&make_tf("base_link", "camera", (0, 0)), "test", true)
+            .unwrap();
+
+        assert_eq!(
+            buffer.latest_common_time("odom", "lidar").unwrap(),
+            LookupTime::Time { sec: 2, nanosec: 500 }
+        );
+        assert_eq!(
+            buffer.latest_common_time("odom", "base_link").unwrap(),
+            LookupTime::Time { sec: 3, nanosec: 0 }
+        );
+        assert_eq!(
+            buffer.latest_common_time("base_link", "camera").unwrap(),
+            LookupTime::Latest
+        );
+        assert!(matches!(
+            buffer.latest_common_time("odom", "unknown"),
+            Err(Tf2Error::Lookup { .. })
+        ));
+    }
+
+    fn assert_vec_near(a: [f64; 3], b: [f64; 3], label: &str) {
+        for i in 0..3 {
+            assert!((a[i] - b[i]).abs() < 1e-6, "{label}: {a:?} vs {b:?}");
+        }
+    }
+
+    #[test]
+    fn lookup_velocity_pure_translation() {
+        let mut buffer = BufferCore::new(Duration::from_secs(10));
+        // 1 m/s along x.
+        for sec in 1..=3 {
+            let mut tf = make_tf("odom", "base_link", (sec, 0));
+            tf.translation = [sec as f64 - 1.0, 0.0, 0.0];
+            buffer.set_transform(&tf, "test", false).unwrap();
+        }
+
+        let v = buffer
+            .lookup_velocity(
+                "base_link",
+                "odom",
+                "odom",
+                [0.0, 0.0, 0.0],
+                LookupTime::Time { sec: 2, nanosec: 0 },
+                Duration::from_secs(1),
+            )
+            .expect("velocity lookup should succeed");
+
+        assert_eq!(v.reference_frame, "odom");
+        assert_eq!((v.stamp_sec, v.stamp_nanosec), (2, 0));
+        assert_vec_near(v.linear, [1.0, 0.0, 0.0], "linear");
+        assert_vec_near(v.angular, [0.0, 0.0, 0.0], "angular");
+    }
+
+    #[test]
+    fn lookup_velocity_pure_rotation() {
+        let mut buffer = BufferCore::new(Duration::from_secs(10));
+        // 0.5 rad/s yaw in place.
+        for sec in 1..=3 {
+            let half_yaw = 0.25 * (sec as f64 - 1.0);
+            let mut tf = make_tf("odom", "base_link", (sec, 0));
+            tf.translation = [0.0, 0.0, 0.0];
+            tf.rotation = [0.0, 0.0, half_yaw.sin(), half_yaw.cos()];
+            buffer.set_transform(&tf, "test", false).unwrap();
+        }
+
+        let at_origin = buffer
+            .lookup_velocity(
+                "base_link",
+                "odom",
+                "odom",
+                [0.0, 0.0, 0.0],
+                LookupTime::Latest,
+                Duration::from_secs(1),
+            )
+            .expect("velocity lookup should succeed");
+        assert_eq!((at_origin.stamp_sec, at_origin.stamp_nanosec), (3, 0));
+        assert_vec_near(at_origin.linear, [0.0, 0.0, 0.0], "linear");
+        assert_vec_near(at_origin.angular, [0.0, 0.0, 0.5], "angular");
+
+        // A point 1 m ahead on the spinning body moves sideways.
+        let off_axis = buffer
+            .lookup_velocity(
+                "base_link",
+                "odom",
+                "odom",
+                [1.0, 0.0, 0.0],
+                LookupTime::Time { sec: 2, nanosec: 0 },
+                Duration::from_secs(1),
+            )
+            .expect("velocity lookup should succeed");
+        assert_vec_near(off_axis.linear, [0.0, 0.5, 0.0], "linear");
+        assert_vec_near(off_axis.angular, [0.0, 0.0, 0.5], "angular");
+    }
+
+    struct ThreadWaker(std::thread::Thread);
+
+    impl std::task::Wake for ThreadWaker {
+        fn wake(self: Arc<Self>) {
+            self.0.unpark();
+        }
+    }
+
+    fn block_on<F: Future>(fut: F) -> F::Output {
+        let waker = Arc::new(ThreadWaker(std::thread::current())).into();
+        let mut cx = Context::from_waker(&waker);
+        let mut fut = std::pin::pin!(fut);
+        loop {
+            if let Poll::Ready(out) = fut.as_mut().poll(&mut cx) {
+                return out;
+            }
+            std::thread::park();
+        }
+    }
+
+    #[test]
+    fn wait_for_transform_resolves_after_set_transform() {
+        let buffer = BufferCore::new(Duration::from_secs(10));
+        let fut = buffer.wait_for_transform("map", "base_link", LookupTime::Latest);
+
+        let mut writer = buffer.clone();
+        let handle = std::thread::spawn(move || {
+            std::thread::sleep(Duration::from_millis(50));
+            writer
+                .set_transform(&make_tf("map", "base_link", (1, 0)), "test", false)
+                .expect("insert should succeed");
+        });
+
+        let tf = block_on(fut).expect("wait should resolve once the transform arrives");
+        assert_eq!(tf.child_frame, "base_link");
+
+        handle.join().unwrap();
+    }
+
+    #[test]
+    fn transform_many_handles_mixed_frames() {
+        let mut buffer = BufferCore::new(Duration::from_secs(10));
+        buffer
+            .set_transform(&make_tf("map", "odom", (1, 0)), "test", true)
+            .unwrap();
+        buffer
+            .set_transform(&make_tf("odom", "base_link", (1, 0)), "test", true)
+            .unwrap();
+
+        let point = |frame: &str| {
+            let mut msg = crate::Stamped::new(frame, (1, 0), geometry_msgs::msg::Point::default());
+            msg.payload.x = 0.5;
+            msg
+        };
+        let msgs = [point("odom"), point("base_link"), point("odom")];
+        let out = buffer
+            .transform_many(&msgs, "map", TimeSpec::FromMsg)
+            .expect("every frame is connected to map");
+        let xs: Vec<f64> = out.iter().map(|m| m.payload.x).collect();
+        assert_eq!(xs, [1.5, 2.5, 1.5]);
+        assert!(out.iter().all(|m| m.frame_id == "map"));
+
+        let err = buffer
+            .transform_many(&[point("odom"), point("camera")], "map", TimeSpec::FromMsg)
+            .unwrap_err();
+        assert!(matches!(err, Tf2Error::Lookup { .. }), "{err:?}");
+    }
+
+    #[test]
+    fn dropping_wait_for_transform_unregisters_waker() {
+        let buffer = BufferCore::new(Duration::from_secs(10));
+        let waker = Arc::new(ThreadWaker(std::thread::current())).into();
+        let mut cx = Context::from_waker(&waker);
+
+        {
+            let mut fut = std::pin::pin!(buffer.wait_for_transform("map", "base_link", LookupTime::Latest));
+            assert!(fut.as_mut().poll(&mut cx).is_pending());
+            assert_eq!(buffer.notifier.waker_count(), 1);
+        }
+
+        assert_eq!(buffer.notifier.waker_count(), 0);
+    }
+}
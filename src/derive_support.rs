@@ -0,0 +1,210 @@
+//! Field-level transforms called by the code `tf2_rs_derive` generates.
+//! Not meant to be used directly.
+//!
+//! Header-less fields (`point`, `vector`, `pose`) use the Rust transform math
+//! directly. A nested stamped message marked `stamped` goes through its own
+//! `Transformable` impl instead.
+
+use crate::error::Tf2Error;
+use crate::transform::{Transformable, TransformablePayload};
+use crate::transform_stamped::TransformStamped;
+
+/// `#[tf2(point)]`: rotated and translated.
+pub trait PointField: Sized {
+    fn transform_point(&self, tf: &TransformStamped) -> Result<Self, Tf2Error>;
+}
+
+/// `#[tf2(vector)]`: rotated only.
+pub trait VectorField: Sized {
+    fn transform_vector(&self, tf: &TransformStamped) -> Result<Self, Tf2Error>;
+}
+
+/// `#[tf2(pose)]`: position and orientation.
+pub trait PoseField: Sized {
+    fn transform_pose(&self, tf: &TransformStamped) -> Result<Self, Tf2Error>;
+}
+
+/// `#[tf2(stamped)]`: a nested message with its own header, transformed by
+/// its `Transformable` impl, which also restamps that header.
+pub trait StampedField: Sized {
+    fn transform_stamped(&self, tf: &TransformStamped) -> Result<Self, Tf2Error>;
+}
+
+impl<T: Transformable> StampedField for T {
+    fn transform_stamped(&self, tf: &TransformStamped) -> Result<Self, Tf2Error> {
+        self.apply_transform(tf)
+    }
+}
+
+impl PointField for [f64; 3] {
+    fn transform_point(&self, tf: &TransformStamped) -> Result<Self, Tf2Error> {
+        Ok(tf.transform_point(*self))
+    }
+}
+
+impl PointField for geometry_msgs::msg::Point {
+    fn transform_point(&self, tf: &TransformStamped) -> Result<Self, Tf2Error> {
+        self.transform_payload(tf)
+    }
+}
+
+impl VectorField for [f64; 3] {
+    fn transform_vector(&self, tf: &TransformStamped) -> Result<Self, Tf2Error> {
+        Ok(tf.transform_vector(*self))
+    }
+}
+
+impl VectorField for geometry_msgs::msg::Vector3 {
+    fn transform_vector(&self, tf: &TransformStamped) -> Result<Self, Tf2Error> {
+        self.transform_payload(tf)
+    }
+}
+
+impl PoseField for geometry_msgs::msg::Pose {
+    fn transform_pose(&self, tf: &TransformStamped) -> Result<Self, Tf2Error> {
+        self.transform_payload(tf)
+    }
+}
+
+/// Lets every field kind also be a `Vec` or an `Option` of that kind.
+macro_rules! impl_for_containers {
+    ($trait:ident, $method:ident) => {
+        impl<T: $trait> $trait for Vec<T> {
+            fn $method(&self, tf: &TransformStamped) -> Result<Self, Tf2Error> {
+                self.iter().map(|v| v.$method(tf)).collect()
+            }
+        }
+
+        impl<T: $trait> $trait for Option<T> {
+            fn $method(&self, tf: &TransformStamped) -> Result<Self, Tf2Error> {
+                self.as_ref().map(|v| v.$method(tf)).transpose()
+            }
+        }
+    };
+}
+
+impl_for_containers!(PointField, transform_point);
+impl_for_containers!(VectorField, transform_vector);
+impl_for_containers!(PoseField, transform_pose);
+impl_for_containers!(StampedField, transform_stamped);
+
+#[cfg(test)]
+mod tests {
+    use crate::{HasHeader, Stamped, TransformStamped, Transformable, TransformablePayload};
+    use geometry_msgs::msg::{Point, Pose, Vector3};
+    use std::f64::consts::FRAC_PI_2;
+
+    #[derive(Clone, Debug, Default, TransformablePayload)]
+    struct Candidate {
+        #[tf2(pose)]
+        grasp: Pose,
+        #[tf2(vector)]
+        approach: [f64; 3],
+        score: f64,
+    }
+
+    #[derive(Clone, Debug, Default, HasHeader, Transformable)]
+    struct DetectionArray {
+        header: std_msgs::msg::Header,
+        #[tf2(point)]
+        centers: Vec<Point>,
+        #[tf2(point)]
+        origin: [f64; 3],
+        #[tf2(vector)]
+        gravity: Option<Vector3>,
+        #[tf2(payload)]
+        candidates: Vec<Candidate>,
+        #[tf2(stamped)]
+        anchors: Vec<Stamped<Point>>,
+        label: String,
+    }
+
+    #[derive(Clone, Debug, Default, HasHeader, Transformable)]
+    struct Tagged(#[tf2(header)] std_msgs::msg::Header, #[tf2(point)] [f64; 3]);
+
+    fn map_odom() -> TransformStamped {
+        let mut tf =
+            TransformStamped::from_euler("map", "odom", [1.0, 0.0, 0.0], [0.0, 0.0, FRAC_PI_2]);
+        tf.stamp_sec = 3;
+        tf
+    }
+
+    fn assert_near(a: [f64; 3], b: [f64; 3]) {
+        for i in 0..3 {
+            assert!((a[i] - b[i]).abs() < 1e-9, "{a:?} vs {b:?}");
+        }
+    }
+
+    #[test]
+    fn derived_transformable_moves_marked_fields() {
+        let mut msg = DetectionArray {
+            centers: vec![Point {
+                x: 1.0,
+                y: 0.0,
+                z: 0.0,
+            }],
+            origin: [0.0, 1.0, 0.0],
+            gravity: Some(Vector3 {
+                x: 1.0,
+                y: 0.0,
+                z: 0.0,
+            }),
+            candidates: vec![Candidate {
+                approach: [1.0, 0.0, 0.0],
+                score: 0.5,
+                ..Default::default()
+            }],
+            anchors: vec![Stamped::new(
+                "odom",
+                (1, 0),
+                Point {
+                    x: 0.0,
+                    y: 2.0,
+                    z: 0.0,
+                },
+            )],
+            label: "mug".to_string(),
+            ..Default::default()
+        };
+        msg.header.frame_id = "odom".to_string();
+        msg.header.stamp.sec = 2;
+        assert_eq!(msg.frame_id(), "odom");
+        assert_eq!(msg.stamp(), (2, 0));
+
+        let out = msg.apply_transform(&map_odom()).unwrap();
+        assert_eq!(out.frame_id(), "map");
+        assert_eq!(out.stamp(), (3, 0));
+        assert_eq!(out.label, "mug");
+
+        let center = &out.centers[0];
+        assert_near([center.x, center.y, center.z], [1.0, 1.0, 0.0]);
+        assert_near(out.origin, [0.0, 0.0, 0.0]);
+        let gravity = out.gravity.unwrap();
+        assert_near([gravity.x, gravity.y, gravity.z], [0.0, 1.0, 0.0]);
+
+        let candidate = &out.candidates[0];
+        assert_eq!(candidate.score, 0.5);
+        assert_near(candidate.approach, [0.0, 1.0, 0.0]);
+        let p = &candidate.grasp.position;
+        assert_near([p.x, p.y, p.z], [1.0, 0.0, 0.0]);
+
+        let anchor = &out.anchors[0];
+        assert_eq!(anchor.frame_id(), "map");
+        assert_eq!(anchor.stamp(), (3, 0));
+        assert_near(
+            [anchor.payload.x, anchor.payload.y, anchor.payload.z],
+            [-1.0, 0.0, 0.0],
+        );
+    }
+
+    #[test]
+    fn derived_header_can_be_a_marked_tuple_field() {
+        let mut msg = Tagged::default();
+        msg.0.frame_id = "odom".to_string();
+        msg.1 = [1.0, 0.0, 0.0];
+
+        let out = msg.apply_transform(&map_odom()).unwrap();
+        assert_eq!(out.frame_id(), "map");
+        assert_near(out.1, [1.0, 1.0, 0.0]);
+    }
+}
@@ -1,28 +1,244 @@
-use crate::ffi::ffi::{Tf2Errc, Tf2Status};
+#[cfg(feature = "tf2-cpp")]
+use crate::ffi::ffi::{Tf2Errc, Tf2Status, Tf2Time};
+use crate::time::LookupTime;
+use std::time::Duration;
 use thiserror::Error;
 
 #[derive(Error, Debug)]
 pub enum Tf2Error {
-    #[error("tf2 lookup error: {0}")]
-    Lookup(String),
-    #[error("tf2 connectivity error: {0}")]
-    Connectivity(String),
-    #[error("tf2 extrapolation error: {0}")]
-    Extrapolation(String),
+    /// A frame is unknown to the buffer. `missing_frames` lists which of the
+    /// requested frames it has never seen.
+    #[error("tf2 lookup error: {message}")]
+    Lookup {
+        target_frame: String,
+        source_frame: String,
+        missing_frames: Vec<String>,
+        message: String,
+    },
+    /// Both frames are known but not part of the same tree.
+    #[error("tf2 connectivity error: {message}")]
+    Connectivity {
+        target_frame: String,
+        source_frame: String,
+        message: String,
+    },
+    /// The requested time is outside the data buffered along the path.
+    #[error("tf2 extrapolation error: {message}")]
+    Extrapolation {
+        target_frame: String,
+        source_frame: String,
+        requested: LookupTime,
+        available: AvailableRange,
+        message: String,
+    },
+    #[error("tf2 timeout: {message}")]
+    Timeout {
+        target_frame: String,
+        source_frame: String,
+        timeout: Duration,
+        message: String,
+    },
     #[error("tf2 invalid argument: {0}")]
     InvalidArgument(String),
+    #[error("invalid transform: {0}")]
+    InvalidTransform(#[from] InvalidTransform),
     #[error("tf2 error: {0}")]
     Other(String),
 }
 
+/// Buffered stamps reported with an extrapolation error. Like tf2's message,
+/// only the bound that was crossed is known (both when a single stamp is
+/// buffered, none when the edge has no data at all).
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct AvailableRange {
+    pub earliest: Option<LookupTime>,
+    pub latest: Option<LookupTime>,
+}
+
+impl Tf2Error {
+    /// The data needed has not arrived yet: an unknown frame, a time newer
+    /// than anything buffered, or a timeout waiting for either.
+    pub fn is_transient(&self) -> bool {
+        match self {
+            Tf2Error::Lookup { .. } | Tf2Error::Timeout { .. } => true,
+            Tf2Error::Extrapolation {
+                requested,
+                available,
+                ..
+            } => !available.is_before(*requested),
+            _ => false,
+        }
+    }
+
+    /// Whether retrying once more transforms arrive can succeed. Unlike
+    /// [`is_transient`](Self::is_transient) this includes frames that are not
+    /// connected yet, which a new edge may join. Invalid arguments and times
+    /// older than the buffer are final.
+    pub fn is_retryable(&self) -> bool {
+        self.is_transient() || matches!(self, Tf2Error::Connectivity { .. })
+    }
+
+    /// tf2's description of the failure, without the variant prefix.
+    pub fn message(&self) -> String {
+        match self {
+            Tf2Error::Lookup { message, .. }
+            | Tf2Error::Connectivity { message, .. }
+            | Tf2Error::Extrapolation { message, .. }
+            | Tf2Error::Timeout { message, .. }
+            | Tf2Error::InvalidArgument(message)
+            | Tf2Error::Other(message) => message.clone(),
+            Tf2Error::InvalidTransform(e) => e.to_string(),
+        }
+    }
+
+    /// Reports the error against the frames of the overall request, e.g.
+    /// for one leg of a lookup through a fixed frame.
+    pub(crate) fn with_frames(mut self, target: &str, source: &str) -> Self {
+        match &mut self {
+            Tf2Error::Lookup {
+                target_frame,
+                source_frame,
+                ..
+            }
+            | Tf2Error::Connectivity {
+                target_frame,
+                source_frame,
+                ..
+            }
+            | Tf2Error::Extrapolation {
+                target_frame,
+                source_frame,
+                ..
+            }
+            | Tf2Error::Timeout {
+                target_frame,
+                source_frame,
+                ..
+            } => {
+                *target_frame = target.to_string();
+                *source_frame = source.to_string();
+            }
+            _ => {}
+        }
+        self
+    }
+}
+
+impl AvailableRange {
+    /// Whether `time` is older than the earliest buffered stamp.
+    fn is_before(&self, time: LookupTime) -> bool {
+        match (
+            time.as_nanos(),
+            self.earliest.and_then(LookupTime::as_nanos),
+        ) {
+            (Some(time), Some(earliest)) => time < earliest,
+            _ => false,
+        }
+    }
+}
+
+/// Problems found by [`TransformStamped::validate`](crate::TransformStamped::validate).
+#[derive(Error, Debug, Clone, PartialEq)]
+pub enum InvalidTransform {
+    #[error("parent frame is empty")]
+    EmptyParentFrame,
+    #[error("child frame is empty")]
+    EmptyChildFrame,
+    #[error("frame '{0}' starts with '/'")]
+    LeadingSlash(String),
+    #[error("parent and child frame are both '{0}'")]
+    SelfTransform(String),
+    #[error("translation {0:?} is not finite")]
+    NonFiniteTranslation([f64; 3]),
+    #[error("rotation {0:?} is not finite")]
+    NonFiniteRotation([f64; 4]),
+    #[error("rotation {rotation:?} is not a unit quaternion (norm {norm})")]
+    NonUnitQuaternion { rotation: [f64; 4], norm: f64 },
+}
+
+/// Error returned by the broadcasters' `send_transform(s)`.
+#[derive(Error, Debug)]
+pub enum BroadcastError {
+    #[error("invalid transform: {0}")]
+    Invalid(#[from] InvalidTransform),
+    #[error(transparent)]
+    Rclrs(#[from] rclrs::RclrsError),
+}
+
+#[cfg(feature = "tf2-cpp")]
 pub fn check_status(st: Tf2Status) -> Result<(), Tf2Error> {
     match st.code {
         Tf2Errc::Ok => Ok(()),
-        Tf2Errc::Lookup => Err(Tf2Error::Lookup(st.message)),
-        Tf2Errc::Connectivity => Err(Tf2Error::Connectivity(st.message)),
-        Tf2Errc::Extrapolation => Err(Tf2Error::Extrapolation(st.message)),
+        Tf2Errc::Lookup => Err(Tf2Error::Lookup {
+            target_frame: st.target_frame,
+            source_frame: st.source_frame,
+            missing_frames: st.missing_frames,
+            message: st.message,
+        }),
+        Tf2Errc::Connectivity => Err(Tf2Error::Connectivity {
+            target_frame: st.target_frame,
+            source_frame: st.source_frame,
+            message: st.message,
+        }),
+        Tf2Errc::Extrapolation => Err(Tf2Error::Extrapolation {
+            target_frame: st.target_frame,
+            source_frame: st.source_frame,
+            requested: stamp(&st.requested_time).unwrap_or(LookupTime::Latest),
+            available: AvailableRange {
+                earliest: stamp(&st.earliest_time),
+                latest: stamp(&st.latest_time),
+            },
+            message: st.message,
+        }),
         Tf2Errc::InvalidArgument => Err(Tf2Error::InvalidArgument(st.message)),
         Tf2Errc::Other => Err(Tf2Error::Other(st.message)),
         _ => Err(Tf2Error::Other(st.message)),
     }
 }
+
+/// The wrapper leaves times it could not determine at zero.
+#[cfg(feature = "tf2-cpp")]
+fn stamp(t: &Tf2Time) -> Option<LookupTime> {
+    match (t.sec, t.nanosec) {
+        (0, 0) => None,
+        (sec, nanosec) => Some(LookupTime::Time { sec, nanosec }),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn extrapolation(requested: i32, earliest: Option<i32>, latest: Option<i32>) -> Tf2Error {
+        let at = |sec| LookupTime::Time { sec, nanosec: 0 };
+        Tf2Error::Extrapolation {
+            target_frame: "odom".to_string(),
+            source_frame: "base_link".to_string(),
+            requested: at(requested),
+            available: AvailableRange {
+                earliest: earliest.map(at),
+                latest: latest.map(at),
+            },
+            message: String::new(),
+        }
+    }
+
+    #[test]
+    fn classifies_transient_and_retryable_errors() {
+        let future = extrapolation(5, None, Some(4));
+        assert!(future.is_transient() && future.is_retryable());
+
+        let past = extrapolation(1, Some(2), None);
+        assert!(!past.is_transient() && !past.is_retryable());
+
+        let connectivity = Tf2Error::Connectivity {
+            target_frame: "map".to_string(),
+            source_frame: "odom".to_string(),
+            message: String::new(),
+        };
+        assert!(!connectivity.is_transient() && connectivity.is_retryable());
+
+        let invalid = Tf2Error::InvalidArgument("empty frame".to_string());
+        assert!(!invalid.is_retryable());
+    }
+}
@@ -14,9 +14,17 @@ pub mod ffi {
     }
 
     #[derive(Clone, Debug, Default)]
+    /// Frames and times are only filled for lookup failures; times tf2 did
+    /// not report stay at zero.
     struct Tf2Status {
         code: Tf2Errc,
         message: String,
+        target_frame: String,
+        source_frame: String,
+        missing_frames: Vec<String>,
+        requested_time: Tf2Time,
+        earliest_time: Tf2Time,
+        latest_time: Tf2Time,
     }
 
     #[derive(Clone, Debug, Default)]
@@ -25,6 +33,12 @@ pub mod ffi {
         diagnostic: String,
     }
 
+    #[derive(Clone, Debug, Default)]
+    struct Tf2ParentResult {
+        has_parent: bool,
+        parent_frame: String,
+    }
+
     #[derive(Clone, Debug, Default)]
     struct Tf2Time {
         sec: i32,
@@ -71,6 +85,73 @@ pub mod ffi {
         orientation_w: f64,
     }
 
+    #[derive(Clone, Debug, Default)]
+    struct Tf2Vector3Stamped {
+        header: Tf2Header,
+        x: f64,
+        y: f64,
+        z: f64,
+    }
+
+    #[derive(Clone, Debug, Default)]
+    struct Tf2QuaternionStamped {
+        header: Tf2Header,
+        x: f64,
+        y: f64,
+        z: f64,
+        w: f64,
+    }
+
+    // geometry_msgs/msg/PoseWithCovarianceStamped, covariance is row-major 6x6.
+    #[derive(Clone, Debug, Default)]
+    struct Tf2PoseWithCovarianceStamped {
+        header: Tf2Header,
+        position_x: f64,
+        position_y: f64,
+        position_z: f64,
+        orientation_x: f64,
+        orientation_y: f64,
+        orientation_z: f64,
+        orientation_w: f64,
+        covariance: Vec<f64>,
+    }
+
+    #[derive(Clone, Debug, Default)]
+    struct Tf2TwistStamped {
+        header: Tf2Header,
+        linear_x: f64,
+        linear_y: f64,
+        linear_z: f64,
+        angular_x: f64,
+        angular_y: f64,
+        angular_z: f64,
+    }
+
+    #[derive(Clone, Debug, Default)]
+    struct Tf2WrenchStamped {
+        header: Tf2Header,
+        force_x: f64,
+        force_y: f64,
+        force_z: f64,
+        torque_x: f64,
+        torque_y: f64,
+        torque_z: f64,
+    }
+
+    // geometry_msgs/msg/Point32
+    #[derive(Clone, Debug, Default)]
+    struct Tf2Point32 {
+        x: f32,
+        y: f32,
+        z: f32,
+    }
+
+    #[derive(Clone, Debug, Default)]
+    struct Tf2PolygonStamped {
+        header: Tf2Header,
+        points: Vec<Tf2Point32>,
+    }
+
     // sensor_msgs/msg/PointField
     #[derive(Clone, Debug, Default)]
     struct Tf2PointField {
@@ -118,6 +199,58 @@ pub mod ffi {
             out: &mut Tf2CanTransformResult,
         ) -> Tf2Status;
 
+        fn lookup_transform_full(
+            self: &BufferCoreWrapper,
+            target_frame: &str,
+            target_time: &Tf2Time,
+            source_frame: &str,
+            source_time: &Tf2Time,
+            fixed_frame: &str,
+            out: &mut Tf2TransformStamped,
+        ) -> Tf2Status;
+
+        fn can_transform_full(
+            self: &BufferCoreWrapper,
+            target_frame: &str,
+            target_time: &Tf2Time,
+            source_frame: &str,
+            source_time: &Tf2Time,
+            fixed_frame: &str,
+            out: &mut Tf2CanTransformResult,
+        ) -> Tf2Status;
+
+        fn frame_names(self: &BufferCoreWrapper, out: &mut Vec<String>) -> Tf2Status;
+
+        fn frame_exists(self: &BufferCoreWrapper, frame_id: &str) -> bool;
+
+        fn get_parent(
+            self: &BufferCoreWrapper,
+            frame_id: &str,
+            time: &Tf2Time,
+            out: &mut Tf2ParentResult,
+        ) -> Tf2Status;
+
+        fn latest_common_time(
+            self: &BufferCoreWrapper,
+            target_frame: &str,
+            source_frame: &str,
+            out: &mut Tf2Time,
+        ) -> Tf2Status;
+
+        fn chain_as_vector(
+            self: &BufferCoreWrapper,
+            target_frame: &str,
+            target_time: &Tf2Time,
+            source_frame: &str,
+            source_time: &Tf2Time,
+            fixed_frame: &str,
+            out: &mut Vec<String>,
+        ) -> Tf2Status;
+
+        fn all_frames_as_yaml(self: &BufferCoreWrapper) -> String;
+
+        fn all_frames_as_string(self: &BufferCoreWrapper) -> String;
+
         fn set_transform(
             self: &BufferCoreWrapper,
             tf: &Tf2TransformStamped,
@@ -126,6 +259,62 @@ pub mod ffi {
             out_ok: &mut bool,
         ) -> Tf2Status;
 
+        // The doTransform wrappers are only used by the message tests, as a
+        // reference for the Rust implementations.
+        fn do_transform_point_stamped(
+            input: &Tf2PointStamped,
+            tf: &Tf2TransformStamped,
+            out: &mut Tf2PointStamped,
+        ) -> Tf2Status;
+
+        fn do_transform_pose_stamped(
+            input: &Tf2PoseStamped,
+            tf: &Tf2TransformStamped,
+            out: &mut Tf2PoseStamped,
+        ) -> Tf2Status;
+
+        fn do_transform_vector3_stamped(
+            input: &Tf2Vector3Stamped,
+            tf: &Tf2TransformStamped,
+            out: &mut Tf2Vector3Stamped,
+        ) -> Tf2Status;
+
+        fn do_transform_quaternion_stamped(
+            input: &Tf2QuaternionStamped,
+            tf: &Tf2TransformStamped,
+            out: &mut Tf2QuaternionStamped,
+        ) -> Tf2Status;
+
+        fn do_transform_transform_stamped(
+            input: &Tf2TransformStamped,
+            tf: &Tf2TransformStamped,
+            out: &mut Tf2TransformStamped,
+        ) -> Tf2Status;
+
+        fn do_transform_pose_with_covariance_stamped(
+            input: &Tf2PoseWithCovarianceStamped,
+            tf: &Tf2TransformStamped,
+            out: &mut Tf2PoseWithCovarianceStamped,
+        ) -> Tf2Status;
+
+        fn do_transform_twist_stamped(
+            input: &Tf2TwistStamped,
+            tf: &Tf2TransformStamped,
+            out: &mut Tf2TwistStamped,
+        ) -> Tf2Status;
+
+        fn do_transform_wrench_stamped(
+            input: &Tf2WrenchStamped,
+            tf: &Tf2TransformStamped,
+            out: &mut Tf2WrenchStamped,
+        ) -> Tf2Status;
+
+        fn do_transform_polygon_stamped(
+            input: &Tf2PolygonStamped,
+            tf: &Tf2TransformStamped,
+            out: &mut Tf2PolygonStamped,
+        ) -> Tf2Status;
+
         fn do_transform_pointcloud2(
             input: &Tf2PointCloud2,
             tf: &Tf2TransformStamped,
@@ -0,0 +1,171 @@
+use std::collections::HashMap;
+
+/// Snapshot of the frame tree known to a `BufferCore`, as returned by
+/// [`BufferCore::frame_graph`](crate::BufferCore::frame_graph).
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct FrameGraph {
+    pub frames: Vec<FrameInfo>,
+}
+
+/// One frame and the edge to its parent, if it has one.
+#[derive(Clone, Debug, PartialEq)]
+pub struct FrameInfo {
+    pub frame_id: String,
+    /// `None` for root frames.
+    pub parent_frame: Option<String>,
+    /// Authority that last set the edge to the parent, as recorded by tf2.
+    pub authority: Option<String>,
+    /// Stamp of the newest transform on the edge, to the microsecond tf2
+    /// reports it with. `None` for roots and for edges whose newest stamp is
+    /// zero, which includes every static edge.
+    pub last_update: Option<(i32, u32)>,
+    /// Average rate (Hz) at which the edge is being updated, as reported by tf2.
+    pub rate: Option<f64>,
+}
+
+impl FrameInfo {
+    pub fn is_root(&self) -> bool {
+        self.parent_frame.is_none()
+    }
+}
+
+impl FrameGraph {
+    pub fn get(&self, frame_id: &str) -> Option<&FrameInfo> {
+        self.frames.iter().find(|f| f.frame_id == frame_id)
+    }
+
+    pub fn contains(&self, frame_id: &str) -> bool {
+        self.get(frame_id).is_some()
+    }
+
+    pub fn parent(&self, frame_id: &str) -> Option<&str> {
+        self.get(frame_id)?.parent_frame.as_deref()
+    }
+
+    pub fn roots(&self) -> impl Iterator<Item = &FrameInfo> {
+        self.frames.iter().filter(|f| f.is_root())
+    }
+
+    pub fn children<'a>(&'a self, frame_id: &'a str) -> impl Iterator<Item = &'a FrameInfo> {
+        self.frames
+            .iter()
+            .filter(move |f| f.parent_frame.as_deref() == Some(frame_id))
+    }
+}
+
+/// One entry of `allFramesAsYAML`: the edge from a frame to its parent.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub(crate) struct YamlEdge {
+    pub parent: Option<String>,
+    pub authority: Option<String>,
+    pub rate: Option<f64>,
+    pub most_recent_transform: Option<(i32, u32)>,
+}
+
+/// Parses the output of `tf2::BufferCore::allFramesAsYAML`, keyed by child frame.
+pub(crate) fn parse_frames_yaml(yaml: &str) -> HashMap<String, YamlEdge> {
+    let mut edges = HashMap::new();
+    let mut current: Option<(String, YamlEdge)> = None;
+
+    for line in yaml.lines() {
+        if line.trim().is_empty() {
+            continue;
+        }
+
+        if !line.starts_with(' ') {
+            if let Some((frame, edge)) = current.take() {
+                edges.insert(frame, edge);
+            }
+            if let Some(frame) = line.trim_end().strip_suffix(':') {
+                current = Some((frame.to_string(), YamlEdge::default()));
+            }
+            continue;
+        }
+
+        let Some((_, edge)) = current.as_mut() else {
+            continue;
+        };
+        let Some((key, value)) = line.trim().split_once(':') else {
+            continue;
+        };
+        let value = value.trim();
+        match key {
+            "parent" => {
+                let parent = value.trim_matches('\'');
+                if !parent.is_empty() {
+                    edge.parent = Some(parent.to_string());
+                }
+            }
+            "broadcaster" => {
+                let authority = value.trim_matches('\'');
+                if !authority.is_empty() && authority != "no recorded authority" {
+                    edge.authority = Some(authority.to_string());
+                }
+            }
+            "rate" => edge.rate = value.parse().ok(),
+            "most_recent_transform" => {
+                edge.most_recent_transform = parse_seconds(value).filter(|stamp| *stamp != (0, 0));
+            }
+            _ => {}
+        }
+    }
+
+    if let Some((frame, edge)) = current {
+        edges.insert(frame, edge);
+    }
+    edges
+}
+
+/// Parses a decimal number of seconds such as `12.500000` into
+/// `(sec, nanosec)` without going through floating point.
+fn parse_seconds(value: &str) -> Option<(i32, u32)> {
+    let (sec, frac) = value.split_once('.').unwrap_or((value, ""));
+    if frac.len() > 9 || !frac.bytes().all(|b| b.is_ascii_digit()) {
+        return None;
+    }
+    let nanosec = format!("{frac:0<9}").parse().ok()?;
+    Some((sec.parse().ok()?, nanosec))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_frames_yaml_reads_edges() {
+        let yaml = "base_link: \n\
+                    \x20 parent: 'odom'\n\
+                    \x20 broadcaster: 'robot_state_publisher'\n\
+                    \x20 rate: 50.000\n\
+                    \x20 most_recent_transform: 12.500001\n\
+                    \x20 oldest_transform: 2.500\n\
+                    \x20 buffer_length: 10.000\n\
+                    camera: \n\
+                    \x20 parent: 'base_link'\n\
+                    \x20 broadcaster: 'no recorded authority'\n\
+                    \x20 rate: 10000.000\n\
+                    \x20 most_recent_transform: 0.000\n\
+                    \x20 oldest_transform: 0.000\n\
+                    \x20 buffer_length: 0.000\n";
+
+        let edges = parse_frames_yaml(yaml);
+        assert_eq!(edges.len(), 2);
+
+        let base = &edges["base_link"];
+        assert_eq!(base.authority.as_deref(), Some("robot_state_publisher"));
+        assert_eq!(base.rate, Some(50.0));
+        assert_eq!(base.parent.as_deref(), Some("odom"));
+        assert_eq!(base.most_recent_transform, Some((12, 500_001_000)));
+
+        let camera = &edges["camera"];
+        assert_eq!(camera.authority, None);
+        assert_eq!(camera.parent.as_deref(), Some("base_link"));
+        assert_eq!(camera.most_recent_transform, None);
+    }
+
+    #[test]
+    fn parse_frames_yaml_handles_empty_buffer() {
+        assert!(parse_frames_yaml("[]").is_empty());
+        assert!(parse_frames_yaml("").is_empty());
+    }
+}
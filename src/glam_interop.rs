@@ -0,0 +1,186 @@
+//! Conversions to and from [glam](https://docs.rs/glam) `f64` types, behind
+//! the `glam` feature.
+
+use crate::transform_stamped::TransformStamped;
+use geometry_msgs::msg::{Point, Pose, Quaternion, Transform, Vector3};
+use glam::{DAffine3, DQuat, DVec3};
+
+/// Converts a `geometry_msgs` value to and from its glam counterpart.
+///
+/// Quaternions are normalized on the way in, so rotations within tf2's
+/// tolerance still give a rigid `DAffine3`.
+pub trait GlamConvert: Sized {
+    type Glam;
+
+    fn to_glam(&self) -> Self::Glam;
+    fn from_glam(value: &Self::Glam) -> Self;
+}
+
+impl GlamConvert for Point {
+    type Glam = DVec3;
+
+    fn to_glam(&self) -> DVec3 {
+        DVec3::new(self.x, self.y, self.z)
+    }
+
+    fn from_glam(p: &DVec3) -> Self {
+        Point {
+            x: p.x,
+            y: p.y,
+            z: p.z,
+        }
+    }
+}
+
+impl GlamConvert for Vector3 {
+    type Glam = DVec3;
+
+    fn to_glam(&self) -> DVec3 {
+        DVec3::new(self.x, self.y, self.z)
+    }
+
+    fn from_glam(v: &DVec3) -> Self {
+        Vector3 {
+            x: v.x,
+            y: v.y,
+            z: v.z,
+        }
+    }
+}
+
+impl GlamConvert for Quaternion {
+    type Glam = DQuat;
+
+    fn to_glam(&self) -> DQuat {
+        quat_to_glam([self.x, self.y, self.z, self.w])
+    }
+
+    fn from_glam(q: &DQuat) -> Self {
+        Quaternion {
+            x: q.x,
+            y: q.y,
+            z: q.z,
+            w: q.w,
+        }
+    }
+}
+
+impl GlamConvert for Pose {
+    type Glam = DAffine3;
+
+    fn to_glam(&self) -> DAffine3 {
+        DAffine3::from_rotation_translation(self.orientation.to_glam(), self.position.to_glam())
+    }
+
+    /// Drops any scale or shear in `affine`.
+    fn from_glam(affine: &DAffine3) -> Self {
+        let (_, rotation, translation) = affine.to_scale_rotation_translation();
+        Pose {
+            position: Point::from_glam(&translation),
+            orientation: Quaternion::from_glam(&rotation),
+        }
+    }
+}
+
+impl GlamConvert for Transform {
+    type Glam = DAffine3;
+
+    fn to_glam(&self) -> DAffine3 {
+        DAffine3::from_rotation_translation(self.rotation.to_glam(), self.translation.to_glam())
+    }
+
+    /// Drops any scale or shear in `affine`.
+    fn from_glam(affine: &DAffine3) -> Self {
+        let (_, rotation, translation) = affine.to_scale_rotation_translation();
+        Transform {
+            translation: Vector3::from_glam(&translation),
+            rotation: Quaternion::from_glam(&rotation),
+        }
+    }
+}
+
+impl From<&TransformStamped> for DAffine3 {
+    fn from(tf: &TransformStamped) -> Self {
+        tf.to_affine()
+    }
+}
+
+impl TransformStamped {
+    /// `parent_frame <- child_frame` as an affine map taking child
+    /// coordinates to parent ones. The stamp and frame ids are dropped.
+    pub fn to_affine(&self) -> DAffine3 {
+        DAffine3::from_rotation_translation(
+            quat_to_glam(self.rotation),
+            DVec3::from_array(self.translation),
+        )
+    }
+
+    /// Builds a transform from the rotation and translation of `affine`,
+    /// stamped zero. Any scale or shear is dropped.
+    pub fn from_affine(
+        parent_frame: impl Into<String>,
+        child_frame: impl Into<String>,
+        affine: &DAffine3,
+    ) -> Self {
+        let (_, rotation, translation) = affine.to_scale_rotation_translation();
+        TransformStamped {
+            stamp_sec: 0,
+            stamp_nanosec: 0,
+            parent_frame: parent_frame.into(),
+            child_frame: child_frame.into(),
+            translation: translation.to_array(),
+            rotation: rotation.to_array(),
+        }
+    }
+}
+
+fn quat_to_glam([x, y, z, w]: [f64; 4]) -> DQuat {
+    DQuat::from_xyzw(x, y, z, w).normalize()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn affine_round_trips_and_matches_transform_point() {
+        let tf = TransformStamped::from_euler("map", "odom", [1.0, -2.0, 0.5], [0.3, 0.2, 1.4]);
+        let affine = tf.to_affine();
+
+        let p = [0.4, 0.1, -0.9];
+        let by_affine = affine.transform_point3(DVec3::from_array(p));
+        let by_tf = tf.transform_point(p);
+        assert!((by_affine - DVec3::from_array(by_tf)).length() < 1e-9);
+
+        let back = TransformStamped::from_affine("map", "odom", &affine);
+        let dot = DQuat::from_array(back.rotation).dot(DQuat::from_array(tf.rotation));
+        assert!((dot.abs() - 1.0).abs() < 1e-9);
+        assert!(
+            (DVec3::from_array(back.translation) - DVec3::from_array(tf.translation)).length()
+                < 1e-9
+        );
+    }
+
+    #[test]
+    fn slightly_off_unit_rotation_stays_rigid() {
+        let mut tf = TransformStamped::from_euler("map", "odom", [1.0, 0.0, 0.0], [0.0, 0.4, 1.0]);
+        let p = [0.4, 0.1, -0.9];
+        let expected = DVec3::from_array(tf.transform_point(p));
+        tf.rotation = tf.rotation.map(|v| v * 1.005);
+
+        let affine = tf.to_affine();
+        assert!((affine.matrix3.determinant() - 1.0).abs() < 1e-12);
+        let pose = Pose {
+            position: Point::default(),
+            orientation: Quaternion {
+                x: tf.rotation[0],
+                y: tf.rotation[1],
+                z: tf.rotation[2],
+                w: tf.rotation[3],
+            },
+        };
+        assert!((pose.to_glam().matrix3.determinant() - 1.0).abs() < 1e-12);
+
+        assert!((affine.transform_point3(DVec3::from_array(p)) - expected).length() < 1e-9);
+    }
+}
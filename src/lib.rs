@@ -1,18 +1,54 @@
 pub mod buffer;
 pub mod listener;
 pub mod broadcaster;
+pub mod frame_graph;
+pub mod stamped;
 pub mod transform_stamped;
+pub mod velocity_stamped;
 
+#[cfg(any(feature = "native", not(feature = "tf2-cpp")))]
+pub mod native;
+
+// The derive macros name this crate `::tf2_rs`, including in its own tests.
+#[cfg(all(test, feature = "derive"))]
+extern crate self as tf2_rs;
+
+#[cfg(feature = "derive")]
+#[doc(hidden)]
+pub mod derive_support;
+
+mod backend;
 mod error;
+#[cfg(feature = "tf2-cpp")]
 mod ffi;
+#[cfg(feature = "tf2-cpp")]
 mod ffi_utils;
+#[cfg(feature = "glam")]
+mod glam_interop;
+mod math;
+#[cfg(feature = "nalgebra")]
+mod nalgebra_interop;
+mod notify;
 mod time;
 mod transform;
 
 pub use buffer::{BufferCore, TransformAvailability};
-pub use broadcaster::{StaticTransformBroadcaster, TransformBroadcaster};
-pub use error::Tf2Error;
-pub use listener::TransformListener;
+pub use broadcaster::{
+    BroadcasterOptions, PeriodicTransformBroadcaster, StaticTransformBroadcaster, TransformBroadcaster,
+};
+pub use error::{AvailableRange, BroadcastError, InvalidTransform, Tf2Error};
+pub use frame_graph::{FrameGraph, FrameInfo};
+#[cfg(feature = "glam")]
+pub use glam_interop::GlamConvert;
+pub use listener::{
+    ListenerTopics, ThreadedTransformListener, TransformListener, TransformListenerOptions,
+};
+#[cfg(feature = "nalgebra")]
+pub use nalgebra_interop::NalgebraConvert;
+pub use stamped::Stamped;
 pub use time::{LookupTime, TimeSpec};
-pub use transform::{HasHeader, Transformable};
-pub use transform_stamped::TransformStamped;
+pub use transform::{HasHeader, Transformable, TransformablePayload};
+#[cfg(feature = "derive")]
+pub use tf2_rs_derive::{HasHeader, Transformable, TransformablePayload};
+pub use transform_stamped::{QUATERNION_RESCALE_TOLERANCE, TransformStamped};
+pub use velocity_stamped::VelocityStamped;
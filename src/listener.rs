@@ -1,36 +1,143 @@
 use crate::buffer::BufferCore;
-use rclrs::{IntoPrimitiveOptions, WorkerSubscription, log_error};
+use rclrs::{
+    ExecutorCommands, IntoPrimitiveOptions, MessageInfo, QOS_PROFILE_DEFAULT, QoSProfile,
+    RclrsError, SpinOptions, WorkerSubscription, log_error,
+};
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::{Arc, mpsc};
+use std::thread::JoinHandle;
+use std::time::Duration;
 use tf2_msgs::msg::TFMessage;
 
+/// Which of the two TF topics a [`TransformListener`] subscribes to.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum ListenerTopics {
+    #[default]
+    Both,
+    DynamicOnly,
+    StaticOnly,
+}
+
+impl ListenerTopics {
+    fn wants_dynamic(self) -> bool {
+        self != ListenerTopics::StaticOnly
+    }
+
+    fn wants_static(self) -> bool {
+        self != ListenerTopics::DynamicOnly
+    }
+}
+
+/// Configuration for [`TransformListener::new_with_options`]. The defaults
+/// match [`TransformListener::new`]: `/tf` and `/tf_static`, reliable with a
+/// depth of 100, transient local for the static topic.
+#[derive(Clone, Debug)]
+pub struct TransformListenerOptions {
+    pub tf_topic: String,
+    pub tf_static_topic: String,
+    pub tf_qos: QoSProfile,
+    pub tf_static_qos: QoSProfile,
+    /// Fixed authority recorded by tf2 for every transform the listener
+    /// inserts. `None` (the default) records each message's publisher GID
+    /// instead, so diagnostics and the frame graph show who sent an edge.
+    pub authority: Option<String>,
+    pub topics: ListenerTopics,
+}
+
+impl Default for TransformListenerOptions {
+    fn default() -> Self {
+        Self {
+            tf_topic: "/tf".to_string(),
+            tf_static_topic: "/tf_static".to_string(),
+            tf_qos: QOS_PROFILE_DEFAULT.keep_last(100).reliable(),
+            tf_static_qos: QOS_PROFILE_DEFAULT
+                .keep_last(100)
+                .reliable()
+                .transient_local(),
+            authority: None,
+            topics: ListenerTopics::Both,
+        }
+    }
+}
+
+impl TransformListenerOptions {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn tf_topic(mut self, topic: impl Into<String>) -> Self {
+        self.tf_topic = topic.into();
+        self
+    }
+
+    pub fn tf_static_topic(mut self, topic: impl Into<String>) -> Self {
+        self.tf_static_topic = topic.into();
+        self
+    }
+
+    /// Listens on `<namespace>/tf` and `<namespace>/tf_static`, e.g.
+    /// `/robot1/tf` for `namespace("/robot1")`.
+    pub fn namespace(self, namespace: &str) -> Self {
+        let namespace = namespace.trim_end_matches('/');
+        self.tf_topic(format!("{namespace}/tf"))
+            .tf_static_topic(format!("{namespace}/tf_static"))
+    }
+
+    pub fn tf_qos(mut self, qos: QoSProfile) -> Self {
+        self.tf_qos = qos;
+        self
+    }
+
+    pub fn tf_static_qos(mut self, qos: QoSProfile) -> Self {
+        self.tf_static_qos = qos;
+        self
+    }
+
+    pub fn authority(mut self, authority: impl Into<String>) -> Self {
+        self.authority = Some(authority.into());
+        self
+    }
+
+    pub fn topics(mut self, topics: ListenerTopics) -> Self {
+        self.topics = topics;
+        self
+    }
+}
+
 pub struct TransformListener {
-    _tf_sub: WorkerSubscription<TFMessage, BufferCore>,
-    _tf_static_sub: WorkerSubscription<TFMessage, BufferCore>,
+    _tf_sub: Option<WorkerSubscription<TFMessage, BufferCore>>,
+    _tf_static_sub: Option<WorkerSubscription<TFMessage, BufferCore>>,
 }
 
 impl TransformListener {
     pub fn new(node: &rclrs::Node, buffer: BufferCore) -> Result<Self, rclrs::RclrsError> {
-        let buf_tf = buffer.clone();
-
-        let worker = node.create_worker(buf_tf);
+        Self::new_with_options(node, buffer, TransformListenerOptions::default())
+    }
 
-        let logger_cb = node.logger().clone();
-        let tf_sub =
-            worker.create_subscription("/tf".keep_last(100).reliable(), move |buf: &mut BufferCore, msg: TFMessage| {
-                buf.ingest_tf_message(msg, "tf2_rs", false, |e| {
-                    log_error!(&logger_cb, "Tf2 bindings error on set_transform:  {}", e)
-                });
-            })?;
+    pub fn new_with_options(
+        node: &rclrs::Node,
+        buffer: BufferCore,
+        options: TransformListenerOptions,
+    ) -> Result<Self, rclrs::RclrsError> {
+        let worker = node.create_worker(buffer);
 
-        let logger_cb = node.logger().clone();
+        let tf_sub = if options.topics.wants_dynamic() {
+            Some(worker.create_subscription(
+                options.tf_topic.as_str().qos(options.tf_qos),
+                ingest_callback(node, options.authority.clone(), false),
+            )?)
+        } else {
+            None
+        };
 
-        let tf_static_sub = worker.create_subscription(
-            "/tf_static".keep_last(100).reliable().transient_local(),
-            move |buf: &mut BufferCore, msg: TFMessage| {
-                buf.ingest_tf_message(msg, "tf2_rs", true, |e| {
-                    log_error!(&logger_cb, "Tf2 bindings error on set_transform:  {}", e)
-                });
-            },
-        )?;
+        let tf_static_sub = if options.topics.wants_static() {
+            Some(worker.create_subscription(
+                options.tf_static_topic.as_str().qos(options.tf_static_qos),
+                ingest_callback(node, options.authority.clone(), true),
+            )?)
+        } else {
+            None
+        };
 
         Ok(Self {
             _tf_sub: tf_sub,
@@ -38,3 +145,158 @@ impl TransformListener {
         })
     }
 }
+
+fn ingest_callback(
+    node: &rclrs::Node,
+    authority: Option<String>,
+    is_static: bool,
+) -> impl Fn(&mut BufferCore, TFMessage, MessageInfo) + Send + Sync + 'static {
+    let logger_cb = node.logger().clone();
+    move |buf: &mut BufferCore, msg: TFMessage, info: MessageInfo| {
+        let publisher;
+        let authority = match &authority {
+            Some(authority) => authority.as_str(),
+            None => {
+                publisher = gid_authority(&info.publisher_gid.data);
+                publisher.as_str()
+            }
+        };
+        buf.ingest_tf_message(msg, authority, is_static, |e| {
+            log_error!(&logger_cb, "Tf2 bindings error on set_transform:  {}", e)
+        });
+    }
+}
+
+/// Hex form of a publisher GID, without the zero padding rmw leaves at the
+/// end of the storage.
+fn gid_authority(gid: &[u8]) -> String {
+    let len = gid.iter().rposition(|b| *b != 0).map_or(0, |i| i + 1);
+    let hex: String = gid[..len].iter().map(|b| format!("{b:02x}")).collect();
+    format!("gid:{hex}")
+}
+
+/// A [`TransformListener`] on its own node and executor, spun on a background
+/// thread (tf2_ros' `spin_thread` mode). TF keeps flowing into the buffer even
+/// while the caller's executor is stuck in a long-running callback.
+///
+/// Dropping it stops the executor and joins the thread.
+pub struct ThreadedTransformListener {
+    buffer: BufferCore,
+    stop: Arc<AtomicBool>,
+    commands: Arc<ExecutorCommands>,
+    thread: Option<JoinHandle<()>>,
+}
+
+impl ThreadedTransformListener {
+    pub fn new(context: &rclrs::Context, buffer: BufferCore) -> Result<Self, RclrsError> {
+        Self::new_with_options(context, buffer, TransformListenerOptions::default())
+    }
+
+    pub fn new_with_options(
+        context: &rclrs::Context,
+        buffer: BufferCore,
+        options: TransformListenerOptions,
+    ) -> Result<Self, RclrsError> {
+        static INSTANCE: AtomicUsize = AtomicUsize::new(0);
+        let node_name = format!(
+            "transform_listener_impl_{}_{}",
+            std::process::id(),
+            INSTANCE.fetch_add(1, Ordering::Relaxed)
+        );
+
+        let stop = Arc::new(AtomicBool::new(false));
+        let (tx, rx) = mpsc::channel();
+        let context = context.clone();
+        let thread_buffer = buffer.clone();
+        let thread_stop = stop.clone();
+
+        let thread = std::thread::Builder::new()
+            .name("tf2_rs_listener".to_string())
+            .spawn(move || {
+                let mut executor = context.create_basic_executor();
+                let setup = executor.create_node(node_name.as_str()).and_then(|node| {
+                    let listener =
+                        TransformListener::new_with_options(&node, thread_buffer, options)?;
+                    Ok((node, listener))
+                });
+                let (node, _listener) = match setup {
+                    Ok(setup) => setup,
+                    Err(e) => {
+                        let _ = tx.send(Err(e));
+                        return;
+                    }
+                };
+                if tx.send(Ok(executor.commands().clone())).is_err() {
+                    return;
+                }
+
+                // A halt that lands before `spin` starts is not seen by it,
+                // so spin in slices and re-check the stop flag.
+                while !thread_stop.load(Ordering::Acquire) {
+                    let opts = SpinOptions::default().timeout(Duration::from_millis(100));
+                    for e in executor.spin(opts) {
+                        log_error!(node.logger(), "TF listener thread error: {}", e);
+                    }
+                }
+            })
+            .expect("failed to spawn the TF listener thread");
+
+        let commands = match rx.recv() {
+            Ok(Ok(commands)) => commands,
+            Ok(Err(e)) => {
+                let _ = thread.join();
+                return Err(e);
+            }
+            Err(_) => {
+                // The thread panicked during setup; surface the panic here.
+                if let Err(panic) = thread.join() {
+                    std::panic::resume_unwind(panic);
+                }
+                unreachable!("listener thread exited without reporting its setup");
+            }
+        };
+
+        Ok(Self {
+            buffer,
+            stop,
+            commands,
+            thread: Some(thread),
+        })
+    }
+
+    /// The buffer the listener thread fills; clones share its data.
+    pub fn buffer(&self) -> &BufferCore {
+        &self.buffer
+    }
+}
+
+impl Drop for ThreadedTransformListener {
+    fn drop(&mut self) {
+        self.stop.store(true, Ordering::Release);
+        self.commands.halt_spinning();
+        if let Some(thread) = self.thread.take() {
+            let _ = thread.join();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn namespace_prefixes_both_topics() {
+        let options = TransformListenerOptions::new().namespace("/robot1/");
+        assert_eq!(options.tf_topic, "/robot1/tf");
+        assert_eq!(options.tf_static_topic, "/robot1/tf_static");
+        assert_eq!(options.authority, None);
+        assert_eq!(options.topics, ListenerTopics::Both);
+    }
+
+    #[test]
+    fn gid_authority_drops_padding() {
+        let mut gid = [0u8; 24];
+        gid[..4].copy_from_slice(&[0x01, 0x0f, 0x00, 0xa3]);
+        assert_eq!(gid_authority(&gid), "gid:010f00a3");
+    }
+}
@@ -0,0 +1,192 @@
+//! Small vector / quaternion helpers for the transform math done on the Rust
+//! side. Quaternions are `[x, y, z, w]`, like `TransformStamped::rotation`.
+
+pub(crate) type Vec3 = [f64; 3];
+pub(crate) type Quat = [f64; 4];
+
+pub(crate) fn add(a: Vec3, b: Vec3) -> Vec3 {
+    [a[0] + b[0], a[1] + b[1], a[2] + b[2]]
+}
+
+pub(crate) fn sub(a: Vec3, b: Vec3) -> Vec3 {
+    [a[0] - b[0], a[1] - b[1], a[2] - b[2]]
+}
+
+pub(crate) fn scale(v: Vec3, s: f64) -> Vec3 {
+    [v[0] * s, v[1] * s, v[2] * s]
+}
+
+pub(crate) fn dot(a: Vec3, b: Vec3) -> f64 {
+    a[0] * b[0] + a[1] * b[1] + a[2] * b[2]
+}
+
+pub(crate) fn cross(a: Vec3, b: Vec3) -> Vec3 {
+    [
+        a[1] * b[2] - a[2] * b[1],
+        a[2] * b[0] - a[0] * b[2],
+        a[0] * b[1] - a[1] * b[0],
+    ]
+}
+
+pub(crate) fn norm(v: Vec3) -> f64 {
+    dot(v, v).sqrt()
+}
+
+/// Hamilton product `a * b`: rotate by `b`, then by `a`.
+pub(crate) fn quat_mul(a: Quat, b: Quat) -> Quat {
+    let [ax, ay, az, aw] = a;
+    let [bx, by, bz, bw] = b;
+    [
+        aw * bx + ax * bw + ay * bz - az * by,
+        aw * by - ax * bz + ay * bw + az * bx,
+        aw * bz + ax * by - ay * bx + az * bw,
+        aw * bw - ax * bx - ay * by - az * bz,
+    ]
+}
+
+pub(crate) fn quat_conjugate(q: Quat) -> Quat {
+    [-q[0], -q[1], -q[2], q[3]]
+}
+
+pub(crate) fn quat_norm(q: Quat) -> f64 {
+    (q[0] * q[0] + q[1] * q[1] + q[2] * q[2] + q[3] * q[3]).sqrt()
+}
+
+/// Rotates `v` by the unit quaternion `q`.
+pub(crate) fn quat_rotate(q: Quat, v: Vec3) -> Vec3 {
+    let u = [q[0], q[1], q[2]];
+    let t = scale(cross(u, v), 2.0);
+    add(add(v, scale(t, q[3])), cross(u, t))
+}
+
+/// Unit axis and angle in `[0, pi]` of the unit quaternion `q`. The axis is
+/// arbitrary (`+x`) for the identity.
+pub(crate) fn quat_to_axis_angle(q: Quat) -> (Vec3, f64) {
+    // q and -q are the same rotation; pick the one with the short angle.
+    let q = if q[3] < 0.0 {
+        [-q[0], -q[1], -q[2], -q[3]]
+    } else {
+        q
+    };
+    let v = [q[0], q[1], q[2]];
+    let s = norm(v);
+    if s < 1e-12 {
+        return ([1.0, 0.0, 0.0], 0.0);
+    }
+    (scale(v, 1.0 / s), 2.0 * s.atan2(q[3]))
+}
+
+pub(crate) const QUAT_IDENTITY: Quat = [0.0, 0.0, 0.0, 1.0];
+
+/// Linear interpolation, `t = 0` gives `a`.
+pub(crate) fn lerp(a: Vec3, b: Vec3, t: f64) -> Vec3 {
+    add(a, scale(sub(b, a), t))
+}
+
+/// Spherical interpolation along the short arc, `t = 0` gives `a`. Same
+/// formulation as `tf2::Quaternion::slerp`, including falling back to `a`
+/// when the two are (anti)parallel.
+pub(crate) fn quat_slerp(a: Quat, b: Quat, t: f64) -> Quat {
+    let magnitude = (quat_norm(a) * quat_norm(b)).max(f64::MIN_POSITIVE);
+    let product = (a[0] * b[0] + a[1] * b[1] + a[2] * b[2] + a[3] * b[3]) / magnitude;
+    if product.abs() >= 1.0 - f64::EPSILON {
+        return a;
+    }
+    let theta = product.abs().acos();
+    let d = theta.sin();
+    let sign = if product < 0.0 { -1.0 } else { 1.0 };
+    let s0 = ((1.0 - t) * theta).sin() / d;
+    let s1 = (t * theta).sin() / d * sign;
+    [
+        a[0] * s0 + b[0] * s1,
+        a[1] * s0 + b[1] * s1,
+        a[2] * s0 + b[2] * s1,
+        a[3] * s0 + b[3] * s1,
+    ]
+}
+
+/// Rotation from fixed-axis roll (x), pitch (y) and yaw (z), applied in that
+/// order, as `tf2::Quaternion::setRPY`.
+pub(crate) fn quat_from_rpy(rpy: Vec3) -> Quat {
+    let (sr, cr) = (rpy[0] / 2.0).sin_cos();
+    let (sp, cp) = (rpy[1] / 2.0).sin_cos();
+    let (sy, cy) = (rpy[2] / 2.0).sin_cos();
+    [
+        sr * cp * cy - cr * sp * sy,
+        cr * sp * cy + sr * cp * sy,
+        cr * cp * sy - sr * sp * cy,
+        cr * cp * cy + sr * sp * sy,
+    ]
+}
+
+/// Inverse of [`quat_from_rpy`], with pitch in `[-pi/2, pi/2]`.
+pub(crate) fn quat_to_rpy(q: Quat) -> Vec3 {
+    let [x, y, z, w] = q;
+    let roll = (2.0 * (w * x + y * z)).atan2(1.0 - 2.0 * (x * x + y * y));
+    let pitch = (2.0 * (w * y - z * x)).clamp(-1.0, 1.0).asin();
+    let yaw = (2.0 * (w * z + x * y)).atan2(1.0 - 2.0 * (y * y + z * z));
+    [roll, pitch, yaw]
+}
+
+/// Row-major rotation matrix of the unit quaternion `q`.
+pub(crate) fn quat_to_matrix(q: Quat) -> [[f64; 3]; 3] {
+    let [x, y, z, w] = q;
+    [
+        [
+            1.0 - 2.0 * (y * y + z * z),
+            2.0 * (x * y - z * w),
+            2.0 * (x * z + y * w),
+        ],
+        [
+            2.0 * (x * y + z * w),
+            1.0 - 2.0 * (x * x + z * z),
+            2.0 * (y * z - x * w),
+        ],
+        [
+            2.0 * (x * z - y * w),
+            2.0 * (y * z + x * w),
+            1.0 - 2.0 * (x * x + y * y),
+        ],
+    ]
+}
+
+/// Unit quaternion of a row-major rotation matrix, picking the numerically
+/// stable branch like `tf2::Matrix3x3::getRotation`.
+pub(crate) fn quat_from_matrix(m: [[f64; 3]; 3]) -> Quat {
+    let trace = m[0][0] + m[1][1] + m[2][2];
+    let q = if trace > 0.0 {
+        let s = (trace + 1.0).sqrt() * 2.0;
+        [
+            (m[2][1] - m[1][2]) / s,
+            (m[0][2] - m[2][0]) / s,
+            (m[1][0] - m[0][1]) / s,
+            0.25 * s,
+        ]
+    } else if m[0][0] > m[1][1] && m[0][0] > m[2][2] {
+        let s = (1.0 + m[0][0] - m[1][1] - m[2][2]).sqrt() * 2.0;
+        [
+            0.25 * s,
+            (m[0][1] + m[1][0]) / s,
+            (m[0][2] + m[2][0]) / s,
+            (m[2][1] - m[1][2]) / s,
+        ]
+    } else if m[1][1] > m[2][2] {
+        let s = (1.0 + m[1][1] - m[0][0] - m[2][2]).sqrt() * 2.0;
+        [
+            (m[0][1] + m[1][0]) / s,
+            0.25 * s,
+            (m[1][2] + m[2][1]) / s,
+            (m[0][2] - m[2][0]) / s,
+        ]
+    } else {
+        let s = (1.0 + m[2][2] - m[0][0] - m[1][1]).sqrt() * 2.0;
+        [
+            (m[0][2] + m[2][0]) / s,
+            (m[1][2] + m[2][1]) / s,
+            0.25 * s,
+            (m[1][0] - m[0][1]) / s,
+        ]
+    };
+    let n = quat_norm(q);
+    [q[0] / n, q[1] / n, q[2] / n, q[3] / n]
+}
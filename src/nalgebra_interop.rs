@@ -0,0 +1,182 @@
+//! Conversions to and from [nalgebra](https://nalgebra.org) types, behind the
+//! `nalgebra` feature.
+
+use crate::buffer::BufferCore;
+use crate::error::Tf2Error;
+use crate::time::LookupTime;
+use crate::transform_stamped::TransformStamped;
+use geometry_msgs::msg::{Point, Pose, Quaternion, Transform, Vector3};
+use nalgebra::{Isometry3, Point3, Translation3, UnitQuaternion, Vector3 as NaVector3};
+
+/// Converts a `geometry_msgs` value to and from its nalgebra counterpart.
+///
+/// Quaternions are normalized on the way in, as `UnitQuaternion` requires.
+pub trait NalgebraConvert: Sized {
+    type Nalgebra;
+
+    fn to_nalgebra(&self) -> Self::Nalgebra;
+    fn from_nalgebra(value: &Self::Nalgebra) -> Self;
+}
+
+impl NalgebraConvert for Point {
+    type Nalgebra = Point3<f64>;
+
+    fn to_nalgebra(&self) -> Point3<f64> {
+        Point3::new(self.x, self.y, self.z)
+    }
+
+    fn from_nalgebra(p: &Point3<f64>) -> Self {
+        Point {
+            x: p.x,
+            y: p.y,
+            z: p.z,
+        }
+    }
+}
+
+impl NalgebraConvert for Vector3 {
+    type Nalgebra = NaVector3<f64>;
+
+    fn to_nalgebra(&self) -> NaVector3<f64> {
+        NaVector3::new(self.x, self.y, self.z)
+    }
+
+    fn from_nalgebra(v: &NaVector3<f64>) -> Self {
+        Vector3 {
+            x: v.x,
+            y: v.y,
+            z: v.z,
+        }
+    }
+}
+
+impl NalgebraConvert for Quaternion {
+    type Nalgebra = UnitQuaternion<f64>;
+
+    fn to_nalgebra(&self) -> UnitQuaternion<f64> {
+        quat_to_nalgebra([self.x, self.y, self.z, self.w])
+    }
+
+    fn from_nalgebra(q: &UnitQuaternion<f64>) -> Self {
+        let [x, y, z, w] = quat_from_nalgebra(q);
+        Quaternion { x, y, z, w }
+    }
+}
+
+impl NalgebraConvert for Pose {
+    type Nalgebra = Isometry3<f64>;
+
+    fn to_nalgebra(&self) -> Isometry3<f64> {
+        Isometry3::from_parts(
+            Translation3::from(self.position.to_nalgebra().coords),
+            self.orientation.to_nalgebra(),
+        )
+    }
+
+    fn from_nalgebra(iso: &Isometry3<f64>) -> Self {
+        Pose {
+            position: Point::from_nalgebra(&iso.translation.vector.into()),
+            orientation: Quaternion::from_nalgebra(&iso.rotation),
+        }
+    }
+}
+
+impl NalgebraConvert for Transform {
+    type Nalgebra = Isometry3<f64>;
+
+    fn to_nalgebra(&self) -> Isometry3<f64> {
+        Isometry3::from_parts(
+            Translation3::from(self.translation.to_nalgebra()),
+            self.rotation.to_nalgebra(),
+        )
+    }
+
+    fn from_nalgebra(iso: &Isometry3<f64>) -> Self {
+        Transform {
+            translation: Vector3::from_nalgebra(&iso.translation.vector),
+            rotation: Quaternion::from_nalgebra(&iso.rotation),
+        }
+    }
+}
+
+impl From<&TransformStamped> for Isometry3<f64> {
+    fn from(tf: &TransformStamped) -> Self {
+        tf.to_isometry()
+    }
+}
+
+impl TransformStamped {
+    /// `parent_frame <- child_frame` as an isometry taking child coordinates
+    /// to parent ones. The stamp and frame ids are dropped.
+    pub fn to_isometry(&self) -> Isometry3<f64> {
+        let [x, y, z] = self.translation;
+        Isometry3::from_parts(Translation3::new(x, y, z), quat_to_nalgebra(self.rotation))
+    }
+
+    /// Builds a transform from an isometry, stamped zero.
+    pub fn from_isometry(
+        parent_frame: impl Into<String>,
+        child_frame: impl Into<String>,
+        iso: &Isometry3<f64>,
+    ) -> Self {
+        let t = iso.translation.vector;
+        TransformStamped {
+            stamp_sec: 0,
+            stamp_nanosec: 0,
+            parent_frame: parent_frame.into(),
+            child_frame: child_frame.into(),
+            translation: [t.x, t.y, t.z],
+            rotation: quat_from_nalgebra(&iso.rotation),
+        }
+    }
+}
+
+impl BufferCore {
+    /// [`lookup_transform`](BufferCore::lookup_transform) returning the
+    /// `target <- source` isometry directly.
+    pub fn lookup_isometry(
+        &self,
+        target_frame: &str,
+        source_frame: &str,
+        when: LookupTime,
+    ) -> Result<Isometry3<f64>, Tf2Error> {
+        Ok(self
+            .lookup_transform(target_frame, source_frame, when)?
+            .to_isometry())
+    }
+}
+
+fn quat_to_nalgebra([x, y, z, w]: [f64; 4]) -> UnitQuaternion<f64> {
+    UnitQuaternion::from_quaternion(nalgebra::Quaternion::new(w, x, y, z))
+}
+
+fn quat_from_nalgebra(q: &UnitQuaternion<f64>) -> [f64; 4] {
+    [q.i, q.j, q.k, q.w]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn isometry_round_trips_and_matches_transform_point() {
+        let tf = TransformStamped::from_euler("map", "odom", [1.0, -2.0, 0.5], [0.3, 0.2, 1.4]);
+        let iso = tf.to_isometry();
+
+        let p = [0.4, 0.1, -0.9];
+        let by_iso = iso * Point3::new(p[0], p[1], p[2]);
+        let by_tf = tf.transform_point(p);
+        for i in 0..3 {
+            assert!((by_iso[i] - by_tf[i]).abs() < 1e-9);
+        }
+
+        let back = TransformStamped::from_isometry("map", "odom", &iso);
+        for i in 0..4 {
+            assert!((back.rotation[i] - tf.rotation[i]).abs() < 1e-12);
+        }
+
+        let pose = Pose::from_nalgebra(&iso);
+        assert!((pose.position.x - 1.0).abs() < 1e-12);
+        assert!((pose.to_nalgebra().translation.vector - iso.translation.vector).norm() < 1e-12);
+    }
+}
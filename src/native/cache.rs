@@ -0,0 +1,246 @@
+//! Per-frame transform storage, following tf2's `TimeCache` and `StaticCache`.
+
+use crate::math::{self, Quat, Vec3};
+
+/// Index of a frame in the tree; `0` means "no frame".
+pub(crate) type FrameId = usize;
+
+/// Transform from a frame to its parent at `stamp` (nanoseconds).
+#[derive(Clone, Debug)]
+pub(crate) struct TransformStorage {
+    pub stamp: i64,
+    pub parent: FrameId,
+    pub translation: Vec3,
+    pub rotation: Quat,
+}
+
+/// Why a cache has no transform at the requested time. `message` is tf2's
+/// extrapolation message, empty if the cache holds no data; the bounds are
+/// the ones that message names.
+#[derive(Clone, Debug)]
+pub(crate) struct CacheMiss {
+    pub message: String,
+    pub requested: i64,
+    pub earliest: Option<i64>,
+    pub latest: Option<i64>,
+}
+
+pub(crate) enum FrameCache {
+    /// A single transform valid at every time.
+    Static(TransformStorage),
+    Dynamic(TimeCache),
+}
+
+impl FrameCache {
+    /// Transform at `time` (`0` for the newest one).
+    pub fn get_data(&self, time: i64) -> Result<TransformStorage, CacheMiss> {
+        match self {
+            FrameCache::Static(storage) => Ok(TransformStorage {
+                stamp: time,
+                ..storage.clone()
+            }),
+            FrameCache::Dynamic(cache) => cache.get_data(time),
+        }
+    }
+
+    /// Parent at `time`, `0` if there is no data for it.
+    pub fn parent(&self, time: i64) -> FrameId {
+        match self {
+            FrameCache::Static(storage) => storage.parent,
+            FrameCache::Dynamic(cache) => cache.parent(time),
+        }
+    }
+
+    /// Newest stamp and the parent at that stamp; static caches report a
+    /// zero stamp and empty caches a zero parent.
+    pub fn latest_time_and_parent(&self) -> (i64, FrameId) {
+        match self {
+            FrameCache::Static(storage) => (0, storage.parent),
+            FrameCache::Dynamic(cache) => cache
+                .storage
+                .last()
+                .map_or((0, 0), |newest| (newest.stamp, newest.parent)),
+        }
+    }
+
+    pub fn latest_timestamp(&self) -> i64 {
+        match self {
+            FrameCache::Static(_) => 0,
+            FrameCache::Dynamic(cache) => cache.storage.last().map_or(0, |s| s.stamp),
+        }
+    }
+
+    pub fn oldest_timestamp(&self) -> i64 {
+        match self {
+            FrameCache::Static(_) => 0,
+            FrameCache::Dynamic(cache) => cache.storage.first().map_or(0, |s| s.stamp),
+        }
+    }
+
+    pub fn list_length(&self) -> usize {
+        match self {
+            FrameCache::Static(_) => 1,
+            FrameCache::Dynamic(cache) => cache.storage.len(),
+        }
+    }
+
+    /// Drops dynamic data. Like tf2, static transforms survive a clear.
+    pub fn clear(&mut self) {
+        if let FrameCache::Dynamic(cache) = self {
+            cache.storage.clear();
+        }
+    }
+}
+
+/// Time-ordered transforms of one edge, oldest first, pruned to
+/// `max_storage_time` behind the newest entry.
+pub(crate) struct TimeCache {
+    max_storage_time: i64,
+    storage: Vec<TransformStorage>,
+}
+
+impl TimeCache {
+    pub fn new(max_storage_time: i64) -> Self {
+        Self {
+            max_storage_time,
+            storage: Vec::new(),
+        }
+    }
+
+    pub fn insert(&mut self, data: TransformStorage) -> Result<(), String> {
+        if let Some(newest) = self.storage.last()
+            && data.stamp < newest.stamp.saturating_sub(self.max_storage_time)
+        {
+            return Err("TF_OLD_DATA ignoring data from the past".to_string());
+        }
+
+        match self.storage.binary_search_by_key(&data.stamp, |s| s.stamp) {
+            Ok(_) => Err("TF_REPEATED_DATA ignoring data with redundant timestamp".to_string()),
+            Err(index) => {
+                self.storage.insert(index, data);
+                self.prune();
+                Ok(())
+            }
+        }
+    }
+
+    fn prune(&mut self) {
+        let Some(newest) = self.storage.last().map(|s| s.stamp) else {
+            return;
+        };
+        let keep_from = self
+            .storage
+            .iter()
+            .position(|s| s.stamp.saturating_add(self.max_storage_time) >= newest)
+            .unwrap_or(self.storage.len());
+        self.storage.drain(..keep_from);
+    }
+
+    fn parent(&self, time: i64) -> FrameId {
+        match self.find_closest(time) {
+            Ok(Closest::One(one)) | Ok(Closest::Two(one, _)) => one.parent,
+            Err(_) => 0,
+        }
+    }
+
+    fn get_data(&self, time: i64) -> Result<TransformStorage, CacheMiss> {
+        match self.find_closest(time)? {
+            Closest::One(one) => Ok(one.clone()),
+            Closest::Two(older, newer) if older.parent == newer.parent => {
+                Ok(interpolate(older, newer, time))
+            }
+            // The parent changed in between; tf2 keeps the older edge.
+            Closest::Two(older, _) => Ok(older.clone()),
+        }
+    }
+
+    fn find_closest(&self, time: i64) -> Result<Closest<'_>, CacheMiss> {
+        let (Some(oldest), Some(newest)) = (self.storage.first(), self.storage.last()) else {
+            return Err(CacheMiss {
+                message: String::new(),
+                requested: time,
+                earliest: None,
+                latest: None,
+            });
+        };
+
+        if time == 0 {
+            return Ok(Closest::One(newest));
+        }
+
+        if self.storage.len() == 1 {
+            return if oldest.stamp == time {
+                Ok(Closest::One(oldest))
+            } else {
+                Err(CacheMiss {
+                    message: format!(
+                        "Lookup would require extrapolation at time {}, but only time {} is in the buffer",
+                        display_time(time),
+                        display_time(oldest.stamp)
+                    ),
+                    requested: time,
+                    earliest: Some(oldest.stamp),
+                    latest: Some(oldest.stamp),
+                })
+            };
+        }
+
+        if time == newest.stamp {
+            return Ok(Closest::One(newest));
+        }
+        if time == oldest.stamp {
+            return Ok(Closest::One(oldest));
+        }
+        if time > newest.stamp {
+            return Err(CacheMiss {
+                message: format!(
+                    "Lookup would require extrapolation into the future.  Requested time {} but the latest data is at time {}",
+                    display_time(time),
+                    display_time(newest.stamp)
+                ),
+                requested: time,
+                earliest: None,
+                latest: Some(newest.stamp),
+            });
+        }
+        if time < oldest.stamp {
+            return Err(CacheMiss {
+                message: format!(
+                    "Lookup would require extrapolation into the past.  Requested time {} but the earliest data is at time {}",
+                    display_time(time),
+                    display_time(oldest.stamp)
+                ),
+                requested: time,
+                earliest: Some(oldest.stamp),
+                latest: None,
+            });
+        }
+
+        // Strictly inside the stored range: `newer_index` is in 1..len.
+        let newer_index = self.storage.partition_point(|s| s.stamp <= time);
+        Ok(Closest::Two(
+            &self.storage[newer_index - 1],
+            &self.storage[newer_index],
+        ))
+    }
+}
+
+enum Closest<'a> {
+    One(&'a TransformStorage),
+    Two(&'a TransformStorage, &'a TransformStorage),
+}
+
+fn interpolate(older: &TransformStorage, newer: &TransformStorage, time: i64) -> TransformStorage {
+    let ratio = (time - older.stamp) as f64 / (newer.stamp - older.stamp) as f64;
+    TransformStorage {
+        stamp: time,
+        parent: older.parent,
+        translation: math::lerp(older.translation, newer.translation, ratio),
+        rotation: math::quat_slerp(older.rotation, newer.rotation, ratio),
+    }
+}
+
+/// Seconds with six decimals, as tf2 prints stamps in its messages.
+pub(crate) fn display_time(ns: i64) -> String {
+    format!("{:.6}", ns as f64 * 1e-9)
+}
@@ -0,0 +1,1469 @@
+//! Pure-Rust port of tf2's time-cached frame tree.
+//!
+//! [`FrameTree`] follows `tf2::BufferCore`: the same cache window and
+//! pruning, interpolation and slerp between stamps, static edges, tree
+//! walking and latest-common-time resolution, and the same error
+//! classification. Without the `tf2-cpp` feature it is what backs
+//! [`BufferCore`](crate::BufferCore); with the `native` feature it is also
+//! available next to the C++ backend, e.g. to diff the two.
+
+mod cache;
+
+use self::cache::{CacheMiss, FrameCache, FrameId, TimeCache, TransformStorage, display_time};
+use crate::buffer::TransformAvailability;
+use crate::error::{AvailableRange, Tf2Error};
+use crate::math::{self, Quat, Vec3};
+use crate::time::LookupTime;
+use crate::transform_stamped::{QUATERNION_NORMALIZATION_TOLERANCE, TransformStamped};
+use std::collections::HashMap;
+use std::fmt::Write as _;
+use std::time::Duration;
+
+/// Deepest chain walked before the tree is assumed to contain a loop.
+const MAX_GRAPH_DEPTH: usize = 1000;
+
+struct Frame {
+    name: String,
+    /// `None` for frames only ever seen as a parent.
+    cache: Option<FrameCache>,
+    authority: Option<String>,
+}
+
+/// Frames and the time-stamped transforms between them.
+pub struct FrameTree {
+    cache_time: i64,
+    frame_ids: HashMap<String, FrameId>,
+    /// Indexed by `FrameId`; slot 0 stands for "no parent".
+    frames: Vec<Frame>,
+}
+
+/// Result of walking the tree between two frames.
+struct Walk {
+    translation: Vec3,
+    rotation: Quat,
+    time: i64,
+}
+
+impl Walk {
+    fn identity(time: i64) -> Self {
+        Self {
+            translation: [0.0; 3],
+            rotation: math::QUAT_IDENTITY,
+            time,
+        }
+    }
+}
+
+/// Transform accumulated from a frame up to the top of a walk.
+#[derive(Clone, Copy)]
+struct ToTop {
+    translation: Vec3,
+    rotation: Quat,
+}
+
+impl ToTop {
+    const IDENTITY: Self = Self {
+        translation: [0.0; 3],
+        rotation: math::QUAT_IDENTITY,
+    };
+
+    /// Prepends one more edge towards the top.
+    fn accumulate(self, edge: &TransformStorage) -> Self {
+        Self {
+            translation: math::add(
+                math::quat_rotate(edge.rotation, self.translation),
+                edge.translation,
+            ),
+            rotation: math::quat_mul(edge.rotation, self.rotation),
+        }
+    }
+
+    fn inverse(self) -> Self {
+        let rotation = math::quat_conjugate(self.rotation);
+        Self {
+            translation: math::quat_rotate(rotation, math::scale(self.translation, -1.0)),
+            rotation,
+        }
+    }
+}
+
+impl FrameTree {
+    pub fn new(cache_time: Duration) -> Self {
+        Self {
+            cache_time: cache_time.as_nanos().try_into().unwrap_or(i64::MAX),
+            frame_ids: HashMap::new(),
+            frames: vec![Frame {
+                name: "NO_PARENT".to_string(),
+                cache: None,
+                authority: None,
+            }],
+        }
+    }
+
+    /// Drops all dynamic data. Frames stay known and static edges are kept.
+    pub fn clear(&mut self) {
+        for frame in &mut self.frames {
+            if let Some(cache) = frame.cache.as_mut() {
+                cache.clear();
+            }
+        }
+    }
+
+    pub fn set_transform(
+        &mut self,
+        tf: &TransformStamped,
+        authority: &str,
+        is_static: bool,
+    ) -> Result<(), Tf2Error> {
+        let parent = tf
+            .parent_frame
+            .strip_prefix('/')
+            .unwrap_or(&tf.parent_frame);
+        let child = tf.child_frame.strip_prefix('/').unwrap_or(&tf.child_frame);
+
+        let mut problems = Vec::new();
+        if child == parent {
+            problems.push(format!(
+                "TF_SELF_TRANSFORM: Ignoring transform from authority \"{authority}\" with frame_id and child_frame_id \"{child}\" because they are the same"
+            ));
+        }
+        if child.is_empty() {
+            problems.push(format!(
+                "TF_NO_CHILD_FRAME_ID: Ignoring transform from authority \"{authority}\" because child_frame_id not set"
+            ));
+        }
+        if parent.is_empty() {
+            problems.push(format!(
+                "TF_NO_FRAME_ID: Ignoring transform with child_frame_id \"{child}\" from authority \"{authority}\" because frame_id not set"
+            ));
+        }
+        if tf
+            .translation
+            .iter()
+            .chain(&tf.rotation)
+            .any(|v| v.is_nan())
+        {
+            problems.push(format!(
+                "TF_NAN_INPUT: Ignoring transform for child_frame_id \"{child}\" from authority \"{authority}\" because of a nan value in the transform"
+            ));
+        }
+        let norm2: f64 = tf.rotation.iter().map(|v| v * v).sum();
+        let normalized = (norm2 - 1.0).abs() < QUATERNION_NORMALIZATION_TOLERANCE;
+        if !normalized {
+            problems.push(format!(
+                "TF_DENORMALIZED_QUATERNION: Ignoring transform for child_frame_id \"{child}\" from authority \"{authority}\" because of an invalid quaternion in the transform"
+            ));
+        }
+        if !problems.is_empty() {
+            return Err(Tf2Error::InvalidArgument(problems.join("; ")));
+        }
+
+        let child_id = self.lookup_or_insert_frame_number(child);
+        let parent_id = self.lookup_or_insert_frame_number(parent);
+        let stamp = LookupTime::Time {
+            sec: tf.stamp_sec,
+            nanosec: tf.stamp_nanosec,
+        }
+        .as_nanos()
+        .unwrap_or_default();
+        let storage = TransformStorage {
+            stamp,
+            parent: parent_id,
+            translation: tf.translation,
+            rotation: tf.rotation,
+        };
+
+        let cache_time = self.cache_time;
+        let frame = &mut self.frames[child_id];
+        let inserted = match (&mut frame.cache, is_static) {
+            (Some(FrameCache::Dynamic(cache)), false) => cache.insert(storage),
+            (slot, false) => {
+                // New frame, or a static edge turning dynamic.
+                let mut cache = TimeCache::new(cache_time);
+                let inserted = cache.insert(storage);
+                *slot = Some(FrameCache::Dynamic(cache));
+                inserted
+            }
+            (slot, true) => {
+                *slot = Some(FrameCache::Static(storage));
+                Ok(())
+            }
+        };
+
+        match inserted {
+            Ok(()) => {
+                frame.authority = Some(authority.to_string());
+                Ok(())
+            }
+            Err(e) => Err(Tf2Error::InvalidArgument(format!(
+                "{e} for frame {child} (parent {parent}) at time {} according to authority {authority}",
+                display_time(stamp)
+            ))),
+        }
+    }
+
+    pub fn lookup_transform(
+        &self,
+        target_frame: &str,
+        source_frame: &str,
+        when: LookupTime,
+    ) -> Result<TransformStamped, Tf2Error> {
+        let walk = self
+            .lookup_transform_impl(target_frame, source_frame, time_ns(when))
+            .map_err(|e| {
+                self.with_missing_frames(e, &[target_frame, source_frame])
+                    .with_frames(target_frame, source_frame)
+            })?;
+        Ok(to_transform_stamped(walk, target_frame, source_frame))
+    }
+
+    /// See [`BufferCore::lookup_transform_full`](crate::BufferCore::lookup_transform_full).
+    pub fn lookup_transform_full(
+        &self,
+        target_frame: &str,
+        target_time: LookupTime,
+        source_frame: &str,
+        source_time: LookupTime,
+        fixed_frame: &str,
+    ) -> Result<TransformStamped, Tf2Error> {
+        let legs = || {
+            self.validate_frame_id("lookupTransform argument target_frame", target_frame)?;
+            self.validate_frame_id("lookupTransform argument source_frame", source_frame)?;
+            self.validate_frame_id("lookupTransform argument fixed_frame", fixed_frame)?;
+            Ok((
+                self.lookup_transform_impl(fixed_frame, source_frame, time_ns(source_time))?,
+                self.lookup_transform_impl(target_frame, fixed_frame, time_ns(target_time))?,
+            ))
+        };
+        let (source_to_fixed, fixed_to_target) = legs().map_err(|e: Tf2Error| {
+            self.with_missing_frames(e, &[target_frame, source_frame, fixed_frame])
+                .with_frames(target_frame, source_frame)
+        })?;
+
+        let walk = Walk {
+            translation: math::add(
+                math::quat_rotate(fixed_to_target.rotation, source_to_fixed.translation),
+                fixed_to_target.translation,
+            ),
+            rotation: math::quat_mul(fixed_to_target.rotation, source_to_fixed.rotation),
+            time: fixed_to_target.time,
+        };
+        Ok(to_transform_stamped(walk, target_frame, source_frame))
+    }
+
+    pub fn check_transform(
+        &self,
+        target_frame: &str,
+        source_frame: &str,
+        when: LookupTime,
+    ) -> TransformAvailability {
+        if target_frame == source_frame {
+            return TransformAvailability::Available;
+        }
+        self.check_ids(&[
+            ("target_frame", target_frame),
+            ("source_frame", source_frame),
+        ])
+        .and_then(|ids| self.can_walk(ids[0], ids[1], time_ns(when)))
+        .map_or_else(unavailable, |()| TransformAvailability::Available)
+    }
+
+    pub fn check_transform_full(
+        &self,
+        target_frame: &str,
+        target_time: LookupTime,
+        source_frame: &str,
+        source_time: LookupTime,
+        fixed_frame: &str,
+    ) -> TransformAvailability {
+        self.check_ids(&[
+            ("target_frame", target_frame),
+            ("source_frame", source_frame),
+            ("fixed_frame", fixed_frame),
+        ])
+        .and_then(|ids| {
+            self.can_walk(ids[0], ids[2], time_ns(target_time))?;
+            self.can_walk(ids[2], ids[1], time_ns(source_time))
+        })
+        .map_or_else(unavailable, |()| TransformAvailability::Available)
+    }
+
+    /// Names of every frame seen so far, in the order they were first seen.
+    pub fn frame_names(&self) -> Vec<String> {
+        self.frames[1..].iter().map(|f| f.name.clone()).collect()
+    }
+
+    pub fn frame_exists(&self, frame_id: &str) -> bool {
+        self.frame_ids.contains_key(frame_id)
+    }
+
+    /// Parent of `frame_id` at `when`, or `None` for root and unknown frames.
+    pub fn parent_frame(&self, frame_id: &str, when: LookupTime) -> Option<String> {
+        let cache = self.frames[self.lookup_frame_number(frame_id)]
+            .cache
+            .as_ref()?;
+        match cache.parent(time_ns(when)) {
+            0 => None,
+            parent => Some(self.frames[parent].name.clone()),
+        }
+    }
+
+    /// tf2's `_getLatestCommonTime`: the newest time at which `target_frame
+    /// <- source_frame` can be looked up, `Latest` if the path between them
+    /// is static only.
+    pub fn latest_common_time(
+        &self,
+        target_frame: &str,
+        source_frame: &str,
+    ) -> Result<LookupTime, Tf2Error> {
+        let target_id = self.lookup_frame_number(target_frame);
+        let source_id = self.lookup_frame_number(source_frame);
+        match self.get_latest_common_time(target_id, source_id) {
+            Ok(0) => Ok(LookupTime::Latest),
+            Ok(ns) => Ok(LookupTime::from_nanos(ns)),
+            Err(e) => Err(self
+                .with_missing_frames(e, &[target_frame, source_frame])
+                .with_frames(target_frame, source_frame)),
+        }
+    }
+
+    /// tf2's `_chainAsVector`: the frames walked to get from `source_frame`
+    /// at `source_time` through `fixed_frame` to `target_frame` at
+    /// `target_time`.
+    pub fn chain_as_vector(
+        &self,
+        target_frame: &str,
+        target_time: LookupTime,
+        source_frame: &str,
+        source_time: LookupTime,
+        fixed_frame: &str,
+    ) -> Result<Vec<String>, Tf2Error> {
+        let target_id = self.lookup_frame_number(target_frame);
+        let source_id = self.lookup_frame_number(source_frame);
+        let fixed_id = self.lookup_frame_number(fixed_frame);
+
+        let walk = || {
+            let mut chain = Vec::new();
+            self.walk_to_top_parent(time_ns(source_time), fixed_id, source_id, Some(&mut chain))?;
+            if time_ns(source_time) == time_ns(target_time) {
+                return Ok(chain);
+            }
+
+            let mut target_chain = Vec::new();
+            self.walk_to_top_parent(
+                time_ns(target_time),
+                target_id,
+                fixed_id,
+                Some(&mut target_chain),
+            )?;
+            let (mut m, mut n) = (target_chain.len(), chain.len());
+            while m > 0 && n > 0 && chain[n - 1] == target_chain[m - 1] {
+                m -= 1;
+                n -= 1;
+            }
+            // Unlike the pruning in `walk_to_top_parent`, tf2 also drops the
+            // last differing frame here.
+            if n > 1 {
+                chain.truncate(n - 2);
+            }
+            chain.extend(&target_chain[..m]);
+            Ok(chain)
+        };
+        let chain = walk().map_err(|e: Tf2Error| {
+            self.with_missing_frames(e, &[target_frame, source_frame, fixed_frame])
+                .with_frames(target_frame, source_frame)
+        })?;
+        Ok(chain
+            .into_iter()
+            .map(|id| self.frames[id].name.clone())
+            .collect())
+    }
+
+    /// Same format as tf2's `allFramesAsYAML`.
+    pub fn all_frames_as_yaml(&self) -> String {
+        let mut out = String::new();
+        if self.frames.len() == 1 {
+            out.push_str("[]");
+        }
+
+        for frame in &self.frames[1..] {
+            let Some(cache) = frame.cache.as_ref() else {
+                continue;
+            };
+            let Ok(newest) = cache.get_data(0) else {
+                continue;
+            };
+
+            let latest = cache.latest_timestamp();
+            let oldest = cache.oldest_timestamp();
+            let rate = cache.list_length() as f64 * 1e9 / (latest - oldest).max(100_000) as f64;
+            let authority = frame
+                .authority
+                .as_deref()
+                .unwrap_or("no recorded authority");
+
+            let _ = writeln!(out, "{}: ", frame.name);
+            let _ = writeln!(out, "  parent: '{}'", self.frames[newest.parent].name);
+            let _ = writeln!(out, "  broadcaster: '{authority}'");
+            let _ = writeln!(out, "  rate: {rate:.3}");
+            let _ = writeln!(out, "  most_recent_transform: {}", display_time(latest));
+            let _ = writeln!(out, "  oldest_transform: {}", display_time(oldest));
+            let _ = writeln!(
+                out,
+                "  buffer_length: {:.3}",
+                (latest - oldest) as f64 * 1e-9
+            );
+        }
+        out
+    }
+
+    /// Same format as tf2's `allFramesAsString`.
+    pub fn all_frames_as_string(&self) -> String {
+        let mut out = String::new();
+        for frame in &self.frames[1..] {
+            let Some(cache) = frame.cache.as_ref() else {
+                continue;
+            };
+            let parent = cache.get_data(0).map_or(0, |newest| newest.parent);
+            let _ = writeln!(
+                out,
+                "Frame {} exists with parent {}.",
+                frame.name, self.frames[parent].name
+            );
+        }
+        out
+    }
+
+    fn lookup_frame_number(&self, frame_id: &str) -> FrameId {
+        self.frame_ids.get(frame_id).copied().unwrap_or(0)
+    }
+
+    fn lookup_or_insert_frame_number(&mut self, frame_id: &str) -> FrameId {
+        if let Some(&id) = self.frame_ids.get(frame_id) {
+            return id;
+        }
+        let id = self.frames.len();
+        self.frames.push(Frame {
+            name: frame_id.to_string(),
+            cache: None,
+            authority: None,
+        });
+        self.frame_ids.insert(frame_id.to_string(), id);
+        id
+    }
+
+    fn validate_frame_id(&self, function: &str, frame_id: &str) -> Result<FrameId, Tf2Error> {
+        check_frame_syntax(function, frame_id)?;
+        match self.lookup_frame_number(frame_id) {
+            0 => Err(Tf2Error::Lookup {
+                target_frame: String::new(),
+                source_frame: String::new(),
+                missing_frames: Vec::new(),
+                message: format!("\"{frame_id}\" passed to {function} does not exist. "),
+            }),
+            id => Ok(id),
+        }
+    }
+
+    /// Fills in which of the requested frames a lookup error found missing.
+    fn with_missing_frames(&self, mut err: Tf2Error, frames: &[&str]) -> Tf2Error {
+        if let Tf2Error::Lookup { missing_frames, .. } = &mut err {
+            *missing_frames = frames
+                .iter()
+                .filter(|frame| !frame.is_empty() && !self.frame_exists(frame))
+                .map(|frame| frame.to_string())
+                .collect();
+        }
+        err
+    }
+
+    /// Resolves the frames of a `canTransform` call, collecting tf2's
+    /// message for every one that does not exist.
+    fn check_ids(&self, frames: &[(&str, &str)]) -> Result<Vec<FrameId>, Tf2Error> {
+        for (arg, frame_id) in frames {
+            check_frame_syntax(&format!("canTransform argument {arg}"), frame_id)?;
+        }
+
+        let ids: Vec<FrameId> = frames
+            .iter()
+            .map(|(_, frame_id)| self.lookup_frame_number(frame_id))
+            .collect();
+        let missing: Vec<(&str, &str)> = frames
+            .iter()
+            .zip(&ids)
+            .filter(|(_, id)| **id == 0)
+            .map(|(frame, _)| *frame)
+            .collect();
+        if missing.is_empty() {
+            return Ok(ids);
+        }
+        let message: Vec<String> = missing
+            .iter()
+            .map(|(arg, frame_id)| format!("canTransform: {arg} {frame_id} does not exist."))
+            .collect();
+        Err(Tf2Error::Lookup {
+            target_frame: frames[0].1.to_string(),
+            source_frame: frames[1].1.to_string(),
+            missing_frames: missing.iter().map(|(_, f)| f.to_string()).collect(),
+            message: message.join(" "),
+        })
+    }
+
+    fn can_walk(&self, target_id: FrameId, source_id: FrameId, time: i64) -> Result<(), Tf2Error> {
+        if target_id == source_id {
+            return Ok(());
+        }
+        self.walk_to_top_parent(time, target_id, source_id, None)
+            .map(|_| ())
+    }
+
+    fn lookup_transform_impl(
+        &self,
+        target_frame: &str,
+        source_frame: &str,
+        time: i64,
+    ) -> Result<Walk, Tf2Error> {
+        if target_frame == source_frame {
+            // Like tf2, this does not require the frame to exist.
+            let time = if time == 0 {
+                self.frames[self.lookup_frame_number(target_frame)]
+                    .cache
+                    .as_ref()
+                    .map_or(0, FrameCache::latest_timestamp)
+            } else {
+                time
+            };
+            return Ok(Walk::identity(time));
+        }
+
+        let target_id =
+            self.validate_frame_id("lookupTransform argument target_frame", target_frame)?;
+        let source_id =
+            self.validate_frame_id("lookupTransform argument source_frame", source_frame)?;
+        self.walk_to_top_parent(time, target_id, source_id, None)
+    }
+
+    /// tf2's `walkToTopParent`: walks up from the source until the chain
+    /// ends, then up from the target until it meets that chain. The frames
+    /// walked through are written to `frame_chain` if given.
+    fn walk_to_top_parent(
+        &self,
+        time: i64,
+        target_id: FrameId,
+        source_id: FrameId,
+        mut frame_chain: Option<&mut Vec<FrameId>>,
+    ) -> Result<Walk, Tf2Error> {
+        if let Some(chain) = frame_chain.as_deref_mut() {
+            chain.clear();
+        }
+        if source_id == target_id {
+            return Ok(Walk::identity(time));
+        }
+
+        let time = if time == 0 {
+            self.get_latest_common_time(target_id, source_id)?
+        } else {
+            time
+        };
+
+        let mut source_to_top = ToTop::IDENTITY;
+        let mut frame = source_id;
+        let mut top_parent = frame;
+        let mut extrapolation_error = None;
+        let mut depth = 0;
+        while frame != 0 {
+            if let Some(chain) = frame_chain.as_deref_mut() {
+                chain.push(frame);
+            }
+            let Some(cache) = self.frames[frame].cache.as_ref() else {
+                // The root of the tree has no cache.
+                top_parent = frame;
+                break;
+            };
+            let edge = match cache.get_data(time) {
+                Ok(edge) => edge,
+                Err(e) => {
+                    // There may still be a path through the target's chain.
+                    top_parent = frame;
+                    extrapolation_error = Some(e);
+                    break;
+                }
+            };
+
+            if frame == target_id {
+                return Ok(Walk {
+                    translation: source_to_top.translation,
+                    rotation: source_to_top.rotation,
+                    time,
+                });
+            }
+
+            source_to_top = source_to_top.accumulate(&edge);
+            top_parent = frame;
+            frame = edge.parent;
+            depth += 1;
+            if depth > MAX_GRAPH_DEPTH {
+                return Err(self.loop_error());
+            }
+        }
+
+        let mut target_to_top = ToTop::IDENTITY;
+        let mut reverse_chain = Vec::new();
+        frame = target_id;
+        depth = 0;
+        while frame != top_parent {
+            if frame_chain.is_some() {
+                reverse_chain.push(frame);
+            }
+            let Some(cache) = self.frames[frame].cache.as_ref() else {
+                break;
+            };
+            let edge = cache
+                .get_data(time)
+                .map_err(|e| self.extrapolation_error(e, source_id, target_id))?;
+
+            if frame == source_id {
+                if let Some(chain) = frame_chain {
+                    reverse_chain.reverse();
+                    *chain = reverse_chain;
+                }
+                let inverse = target_to_top.inverse();
+                return Ok(Walk {
+                    translation: inverse.translation,
+                    rotation: inverse.rotation,
+                    time,
+                });
+            }
+
+            target_to_top = target_to_top.accumulate(&edge);
+            frame = edge.parent;
+            depth += 1;
+            if depth > MAX_GRAPH_DEPTH {
+                return Err(self.loop_error());
+            }
+        }
+
+        if frame != top_parent {
+            return Err(match extrapolation_error {
+                Some(e) => self.extrapolation_error(e, source_id, target_id),
+                None => self.connectivity_error(source_id, target_id),
+            });
+        }
+
+        if let Some(chain) = frame_chain {
+            // tf2 prunes the part both walks share, comparing from the top.
+            let (mut m, mut n) = (reverse_chain.len(), chain.len());
+            while m > 0 && n > 0 && chain[n - 1] == reverse_chain[m - 1] {
+                m -= 1;
+                n -= 1;
+            }
+            if n > 1 {
+                chain.truncate(n);
+            }
+            chain.extend(reverse_chain[..m].iter().rev());
+        }
+
+        let inverse = target_to_top.inverse();
+        Ok(Walk {
+            translation: math::add(
+                math::quat_rotate(inverse.rotation, source_to_top.translation),
+                inverse.translation,
+            ),
+            rotation: math::quat_mul(inverse.rotation, source_to_top.rotation),
+            time,
+        })
+    }
+
+    /// tf2's `getLatestCommonTime`: the oldest of the newest stamps along the
+    /// path, `0` if the path is static only.
+    fn get_latest_common_time(
+        &self,
+        target_id: FrameId,
+        source_id: FrameId,
+    ) -> Result<i64, Tf2Error> {
+        if target_id == 0 || source_id == 0 {
+            // tf2 gives no message for unknown frames here.
+            return Err(Tf2Error::Lookup {
+                target_frame: String::new(),
+                source_frame: String::new(),
+                missing_frames: Vec::new(),
+                message: String::new(),
+            });
+        }
+        if target_id == source_id {
+            return Ok(self.frames[source_id]
+                .cache
+                .as_ref()
+                .map_or(0, FrameCache::latest_timestamp));
+        }
+        let resolve = |common: i64| if common == i64::MAX { 0 } else { common };
+
+        let mut source_chain: Vec<(i64, FrameId)> = Vec::new();
+        let mut common = i64::MAX;
+        let mut frame = source_id;
+        while let Some(cache) = self.frames[frame].cache.as_ref() {
+            let (stamp, parent) = cache.latest_time_and_parent();
+            if parent == 0 {
+                break;
+            }
+            if stamp != 0 {
+                common = common.min(stamp);
+            }
+            source_chain.push((stamp, parent));
+            frame = parent;
+            if frame == target_id {
+                return Ok(resolve(common));
+            }
+            if source_chain.len() > MAX_GRAPH_DEPTH {
+                return Err(self.loop_error());
+            }
+        }
+
+        common = i64::MAX;
+        let mut common_parent = 0;
+        let mut depth = 0;
+        frame = target_id;
+        while let Some(cache) = self.frames[frame].cache.as_ref() {
+            let (stamp, parent) = cache.latest_time_and_parent();
+            if parent == 0 {
+                break;
+            }
+            if stamp != 0 {
+                common = common.min(stamp);
+            }
+            if source_chain.iter().any(|&(_, p)| p == parent) {
+                common_parent = parent;
+                break;
+            }
+            frame = parent;
+            if frame == source_id {
+                return Ok(resolve(common));
+            }
+            depth += 1;
+            if depth > MAX_GRAPH_DEPTH {
+                return Err(self.loop_error());
+            }
+        }
+
+        if common_parent == 0 {
+            return Err(self.connectivity_error(source_id, target_id));
+        }
+
+        for &(stamp, parent) in &source_chain {
+            if stamp != 0 {
+                common = common.min(stamp);
+            }
+            if parent == common_parent {
+                break;
+            }
+        }
+        Ok(resolve(common))
+    }
+
+    fn extrapolation_error(
+        &self,
+        miss: CacheMiss,
+        source_id: FrameId,
+        target_id: FrameId,
+    ) -> Tf2Error {
+        let target_frame = self.frames[target_id].name.clone();
+        let source_frame = self.frames[source_id].name.clone();
+        Tf2Error::Extrapolation {
+            message: format!(
+                "{}, when looking up transform from frame [{source_frame}] to frame [{target_frame}]",
+                miss.message
+            ),
+            target_frame,
+            source_frame,
+            requested: match miss.requested {
+                0 => LookupTime::Latest,
+                ns => LookupTime::from_nanos(ns),
+            },
+            available: AvailableRange {
+                earliest: miss.earliest.map(LookupTime::from_nanos),
+                latest: miss.latest.map(LookupTime::from_nanos),
+            },
+        }
+    }
+
+    fn connectivity_error(&self, source_id: FrameId, target_id: FrameId) -> Tf2Error {
+        let target_frame = self.frames[target_id].name.clone();
+        let source_frame = self.frames[source_id].name.clone();
+        Tf2Error::Connectivity {
+            message: format!(
+                "Could not find a connection between '{target_frame}' and '{source_frame}' because they are not part of the same tree.Tf has two or more unconnected trees."
+            ),
+            target_frame,
+            source_frame,
+        }
+    }
+
+    fn loop_error(&self) -> Tf2Error {
+        Tf2Error::Lookup {
+            target_frame: String::new(),
+            source_frame: String::new(),
+            missing_frames: Vec::new(),
+            message: format!(
+                "The tf tree is invalid because it contains a loop.\n{}",
+                self.all_frames_as_string()
+            ),
+        }
+    }
+}
+
+fn check_frame_syntax(function: &str, frame_id: &str) -> Result<(), Tf2Error> {
+    if frame_id.is_empty() {
+        return Err(Tf2Error::InvalidArgument(format!(
+            "Invalid argument passed to {function} in tf2 frame_ids cannot be empty"
+        )));
+    }
+    if frame_id.starts_with('/') {
+        return Err(Tf2Error::InvalidArgument(format!(
+            "Invalid argument \"{frame_id}\" passed to {function} - in tf2 frame_ids cannot start with a '/' like: "
+        )));
+    }
+    Ok(())
+}
+
+fn unavailable(err: Tf2Error) -> TransformAvailability {
+    let diagnostic = err.message();
+    TransformAvailability::Unavailable {
+        diagnostic: (!diagnostic.is_empty()).then_some(diagnostic),
+    }
+}
+
+/// tf2 stamps are nanoseconds with `0` meaning "latest".
+fn time_ns(when: LookupTime) -> i64 {
+    when.as_nanos().unwrap_or(0)
+}
+
+fn to_transform_stamped(walk: Walk, target_frame: &str, source_frame: &str) -> TransformStamped {
+    let (stamp_sec, stamp_nanosec) = match LookupTime::from_nanos(walk.time) {
+        LookupTime::Time { sec, nanosec } => (sec, nanosec),
+        LookupTime::Latest => (0, 0),
+    };
+    TransformStamped {
+        stamp_sec,
+        stamp_nanosec,
+        parent_frame: target_frame.to_string(),
+        child_frame: source_frame.to_string(),
+        translation: walk.translation,
+        rotation: walk.rotation,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn make_tf(
+        parent_frame: &str,
+        child_frame: &str,
+        sec: i32,
+        x: f64,
+        yaw: f64,
+    ) -> TransformStamped {
+        TransformStamped {
+            stamp_sec: sec,
+            stamp_nanosec: 0,
+            parent_frame: parent_frame.to_string(),
+            child_frame: child_frame.to_string(),
+            translation: [x, 0.0, 0.0],
+            rotation: [0.0, 0.0, (yaw / 2.0).sin(), (yaw / 2.0).cos()],
+        }
+    }
+
+    fn at(sec: i32, nanosec: u32) -> LookupTime {
+        LookupTime::Time { sec, nanosec }
+    }
+
+    #[test]
+    fn interpolates_between_stamps() {
+        let mut tree = FrameTree::new(Duration::from_secs(10));
+        tree.set_transform(&make_tf("odom", "base_link", 1, 0.0, 0.0), "test", false)
+            .unwrap();
+        tree.set_transform(
+            &make_tf("odom", "base_link", 2, 2.0, std::f64::consts::FRAC_PI_2),
+            "test",
+            false,
+        )
+        .unwrap();
+
+        let tf = tree
+            .lookup_transform("odom", "base_link", at(1, 500_000_000))
+            .unwrap();
+        assert!((tf.translation[0] - 1.0).abs() < 1e-9);
+        let yaw = 2.0 * tf.rotation[2].atan2(tf.rotation[3]);
+        assert!((yaw - std::f64::consts::FRAC_PI_4).abs() < 1e-9);
+
+        let latest = tree
+            .lookup_transform("odom", "base_link", LookupTime::Latest)
+            .unwrap();
+        assert_eq!((latest.stamp_sec, latest.stamp_nanosec), (2, 0));
+    }
+
+    #[test]
+    fn classifies_errors_like_tf2() {
+        let mut tree = FrameTree::new(Duration::from_secs(10));
+        tree.set_transform(&make_tf("odom", "base_link", 1, 0.0, 0.0), "test", false)
+            .unwrap();
+        tree.set_transform(&make_tf("odom", "base_link", 2, 1.0, 0.0), "test", false)
+            .unwrap();
+        tree.set_transform(&make_tf("map", "other", 2, 1.0, 0.0), "test", false)
+            .unwrap();
+
+        assert!(matches!(
+            tree.lookup_transform("odom", "base_link", at(3, 0)),
+            Err(Tf2Error::Extrapolation { .. })
+        ));
+        assert!(matches!(
+            tree.lookup_transform("odom", "nope", LookupTime::Latest),
+            Err(Tf2Error::Lookup { .. })
+        ));
+        assert!(matches!(
+            tree.lookup_transform("odom", "other", LookupTime::Latest),
+            Err(Tf2Error::Connectivity { .. })
+        ));
+        assert!(matches!(
+            tree.lookup_transform("/odom", "base_link", LookupTime::Latest),
+            Err(Tf2Error::InvalidArgument(_))
+        ));
+        assert!(matches!(
+            tree.set_transform(&make_tf("odom", "base_link", 1, 5.0, 0.0), "test", false),
+            Err(Tf2Error::InvalidArgument(_))
+        ));
+        assert!(
+            !tree
+                .check_transform("odom", "base_link", at(3, 0))
+                .is_available()
+        );
+    }
+
+    #[test]
+    fn errors_carry_frames_and_times() {
+        let mut tree = FrameTree::new(Duration::from_secs(10));
+        tree.set_transform(&make_tf("odom", "base_link", 1, 0.0, 0.0), "test", false)
+            .unwrap();
+        tree.set_transform(&make_tf("odom", "base_link", 2, 1.0, 0.0), "test", false)
+            .unwrap();
+
+        match tree.lookup_transform("odom", "base_link", at(3, 0)) {
+            Err(Tf2Error::Extrapolation {
+                target_frame,
+                source_frame,
+                requested,
+                available,
+                ..
+            }) => {
+                assert_eq!(
+                    (target_frame.as_str(), source_frame.as_str()),
+                    ("odom", "base_link")
+                );
+                assert_eq!(requested, at(3, 0));
+                assert_eq!(available.latest, Some(at(2, 0)));
+                assert_eq!(available.earliest, None);
+            }
+            other => panic!("expected an extrapolation error, got {other:?}"),
+        }
+
+        match tree.lookup_transform("map", "base_link", LookupTime::Latest) {
+            Err(Tf2Error::Lookup {
+                target_frame,
+                missing_frames,
+                ..
+            }) => {
+                assert_eq!(target_frame, "map");
+                assert_eq!(missing_frames, ["map"]);
+            }
+            other => panic!("expected a lookup error, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn prunes_outside_cache_window() {
+        let mut tree = FrameTree::new(Duration::from_secs(2));
+        for sec in 1..=5 {
+            tree.set_transform(
+                &make_tf("odom", "base_link", sec, sec as f64, 0.0),
+                "test",
+                false,
+            )
+            .unwrap();
+        }
+
+        assert!(tree.lookup_transform("odom", "base_link", at(3, 0)).is_ok());
+        assert!(matches!(
+            tree.lookup_transform("odom", "base_link", at(2, 0)),
+            Err(Tf2Error::Extrapolation { .. })
+        ));
+        assert!(matches!(
+            tree.set_transform(&make_tf("odom", "base_link", 1, 0.0, 0.0), "test", false),
+            Err(Tf2Error::InvalidArgument(_))
+        ));
+    }
+
+    #[test]
+    fn duration_max_keeps_everything() {
+        let mut tree = FrameTree::new(Duration::MAX);
+        for sec in [2, 5, 1_000_000_000] {
+            tree.set_transform(
+                &make_tf("odom", "base_link", sec, sec as f64, 0.0),
+                "test",
+                false,
+            )
+            .unwrap();
+        }
+        tree.set_transform(&make_tf("odom", "base_link", 1, 1.0, 0.0), "test", false)
+            .expect("old data is inside an unbounded cache window");
+
+        let tf = tree
+            .lookup_transform("odom", "base_link", at(1, 0))
+            .unwrap();
+        assert_eq!(tf.translation[0], 1.0);
+        assert!(tree.lookup_transform("odom", "base_link", at(5, 0)).is_ok());
+    }
+
+    #[test]
+    fn walks_through_common_ancestor_with_static_edges() {
+        let mut tree = FrameTree::new(Duration::from_secs(10));
+        tree.set_transform(
+            &make_tf("base_link", "laser", 0, 1.0, std::f64::consts::FRAC_PI_2),
+            "test",
+            true,
+        )
+        .unwrap();
+        tree.set_transform(&make_tf("base_link", "camera", 0, -1.0, 0.0), "test", true)
+            .unwrap();
+
+        // Static-only chains resolve `Latest` to a zero stamp.
+        let tf = tree
+            .lookup_transform("laser", "camera", LookupTime::Latest)
+            .unwrap();
+        assert_eq!((tf.stamp_sec, tf.stamp_nanosec), (0, 0));
+        // The camera sits 2 m behind the laser, which is yawed by 90 degrees.
+        assert!((tf.translation[0] - 0.0).abs() < 1e-9);
+        assert!((tf.translation[1] - 2.0).abs() < 1e-9);
+
+        tree.clear();
+        assert!(tree.lookup_transform("laser", "camera", at(7, 0)).is_ok());
+        assert_eq!(
+            tree.parent_frame("laser", LookupTime::Latest).as_deref(),
+            Some("base_link")
+        );
+        assert_eq!(tree.parent_frame("base_link", LookupTime::Latest), None);
+    }
+
+    #[test]
+    fn chain_as_vector_lists_walked_frames() {
+        let mut tree = FrameTree::new(Duration::from_secs(10));
+        tree.set_transform(&make_tf("map", "odom", 1, 0.0, 0.0), "test", false)
+            .unwrap();
+        tree.set_transform(&make_tf("odom", "base_link", 1, 1.0, 0.0), "test", false)
+            .unwrap();
+        tree.set_transform(&make_tf("base_link", "laser", 0, 0.2, 0.0), "test", true)
+            .unwrap();
+        tree.set_transform(&make_tf("world", "island", 1, 0.0, 0.0), "test", false)
+            .unwrap();
+
+        let chain = |target, source| {
+            tree.chain_as_vector(
+                target,
+                LookupTime::Latest,
+                source,
+                LookupTime::Latest,
+                target,
+            )
+        };
+        assert_eq!(
+            chain("map", "laser").unwrap(),
+            ["laser", "base_link", "odom", "map"]
+        );
+        assert_eq!(
+            chain("laser", "odom").unwrap(),
+            ["odom", "base_link", "laser"]
+        );
+        assert!(chain("laser", "laser").unwrap().is_empty());
+        assert!(matches!(
+            chain("map", "island"),
+            Err(Tf2Error::Connectivity { .. })
+        ));
+    }
+}
+
+/// Runs the same scenario through tf2 (the C++ `BufferCore` backend) and
+/// through `FrameTree`, and checks that the two agree.
+#[cfg(all(test, feature = "tf2-cpp"))]
+mod differential_tests {
+    use super::*;
+    use crate::BufferCore;
+
+    fn edge(
+        parent_frame: &str,
+        child_frame: &str,
+        stamp: (i32, u32),
+        translation: [f64; 3],
+        yaw: f64,
+    ) -> TransformStamped {
+        TransformStamped {
+            stamp_sec: stamp.0,
+            stamp_nanosec: stamp.1,
+            parent_frame: parent_frame.to_string(),
+            child_frame: child_frame.to_string(),
+            translation,
+            rotation: [0.0, 0.0, (yaw / 2.0).sin(), (yaw / 2.0).cos()],
+        }
+    }
+
+    fn assert_same(
+        tf2: &Result<TransformStamped, Tf2Error>,
+        native: &Result<TransformStamped, Tf2Error>,
+        label: &str,
+    ) {
+        match (tf2, native) {
+            (Ok(a), Ok(b)) => {
+                assert_eq!(
+                    (a.stamp_sec, a.stamp_nanosec),
+                    (b.stamp_sec, b.stamp_nanosec),
+                    "{label}: stamp"
+                );
+                for i in 0..3 {
+                    assert!(
+                        (a.translation[i] - b.translation[i]).abs() < 1e-9,
+                        "{label}: translation {a:?} vs {b:?}"
+                    );
+                }
+                // q and -q are the same rotation.
+                let dot: f64 = (0..4).map(|i| a.rotation[i] * b.rotation[i]).sum();
+                assert!(
+                    (dot.abs() - 1.0).abs() < 1e-9,
+                    "{label}: rotation {a:?} vs {b:?}"
+                );
+            }
+            (Err(a), Err(b)) => {
+                assert_eq!(
+                    std::mem::discriminant(a),
+                    std::mem::discriminant(b),
+                    "{label}: {a} vs {b}"
+                );
+                match (a, b) {
+                    (
+                        Tf2Error::Lookup {
+                            missing_frames: a, ..
+                        },
+                        Tf2Error::Lookup {
+                            missing_frames: b, ..
+                        },
+                    ) => assert_eq!(a, b, "{label}: missing frames"),
+                    (
+                        Tf2Error::Extrapolation {
+                            requested: ra,
+                            available: aa,
+                            ..
+                        },
+                        Tf2Error::Extrapolation {
+                            requested: rb,
+                            available: ab,
+                            ..
+                        },
+                    ) => assert_eq!((ra, aa), (rb, ab), "{label}: extrapolation range"),
+                    _ => {}
+                }
+            }
+            _ => panic!("{label}: tf2 gave {tf2:?}, native gave {native:?}"),
+        }
+    }
+
+    #[test]
+    fn lookups_match_tf2() {
+        let mut tf2 = BufferCore::new(Duration::from_secs(10));
+        let mut native = FrameTree::new(Duration::from_secs(10));
+
+        let inserts = [
+            (edge("map", "odom", (1, 0), [1.0, 2.0, 0.0], 0.3), false),
+            (edge("map", "odom", (3, 0), [2.0, 2.0, 0.0], 0.6), false),
+            (
+                edge("odom", "base_link", (2, 0), [0.5, 0.0, 0.0], 1.0),
+                false,
+            ),
+            (
+                edge("odom", "base_link", (4, 0), [1.5, 0.5, 0.0], -1.0),
+                false,
+            ),
+            // Out of order, still inside the cache window.
+            (
+                edge("odom", "base_link", (3, 0), [1.0, 0.2, 0.0], 0.0),
+                false,
+            ),
+            (
+                edge("base_link", "laser", (0, 0), [0.2, 0.0, 0.3], 3.0),
+                true,
+            ),
+            (
+                edge("base_link", "camera", (0, 0), [-0.1, 0.1, 0.5], -2.0),
+                true,
+            ),
+            (
+                edge("world", "island", (2, 500_000_000), [0.0, 0.0, 1.0], 0.0),
+                false,
+            ),
+            // Rejected by both.
+            (edge("odom", "odom", (2, 0), [0.0; 3], 0.0), false),
+            (edge("", "base_link", (2, 0), [0.0; 3], 0.0), false),
+        ];
+        for (i, (tf, is_static)) in inserts.iter().enumerate() {
+            let a = tf2.set_transform(tf, "diff", *is_static);
+            let b = native.set_transform(tf, "diff", *is_static);
+            assert_eq!(a.is_ok(), b.is_ok(), "insert {i}: {a:?} vs {b:?}");
+        }
+        let mut denormalized = edge("map", "odom", (5, 0), [0.0; 3], 0.0);
+        denormalized.rotation = [0.0, 0.0, 0.0, 2.0];
+        assert!(tf2.set_transform(&denormalized, "diff", false).is_err());
+        assert!(native.set_transform(&denormalized, "diff", false).is_err());
+
+        let frames = [
+            "map",
+            "odom",
+            "base_link",
+            "laser",
+            "camera",
+            "world",
+            "island",
+            "ghost",
+        ];
+        let times = [
+            LookupTime::Latest,
+            LookupTime::Time {
+                sec: 0,
+                nanosec: 500_000_000,
+            },
+            LookupTime::Time { sec: 1, nanosec: 0 },
+            LookupTime::Time { sec: 2, nanosec: 0 },
+            LookupTime::Time {
+                sec: 2,
+                nanosec: 250_000_000,
+            },
+            LookupTime::Time { sec: 3, nanosec: 0 },
+            LookupTime::Time {
+                sec: 3,
+                nanosec: 750_000_000,
+            },
+            LookupTime::Time { sec: 9, nanosec: 0 },
+        ];
+
+        for target in frames {
+            for source in frames {
+                for when in times {
+                    let label = format!("{target} <- {source} at {when:?}");
+                    assert_same(
+                        &tf2.lookup_transform(target, source, when),
+                        &native.lookup_transform(target, source, when),
+                        &label,
+                    );
+                    assert_eq!(
+                        tf2.check_transform(target, source, when)
+                            .unwrap()
+                            .is_available(),
+                        native.check_transform(target, source, when).is_available(),
+                        "{label}: availability"
+                    );
+                }
+            }
+        }
+
+        for target in frames {
+            for source in frames {
+                let label = format!("latest common time of {target} and {source}");
+                match (
+                    tf2.latest_common_time(target, source),
+                    native.latest_common_time(target, source),
+                ) {
+                    (Ok(a), Ok(b)) => assert_eq!(a, b, "{label}"),
+                    (Err(a), Err(b)) => assert_eq!(
+                        std::mem::discriminant(&a),
+                        std::mem::discriminant(&b),
+                        "{label}: {a} vs {b}"
+                    ),
+                    (a, b) => panic!("{label}: tf2 gave {a:?}, native gave {b:?}"),
+                }
+            }
+        }
+
+        for (target_time, source_time) in [(times[5], times[3]), (times[0], times[4])] {
+            let label = format!("laser@{target_time:?} <- camera@{source_time:?} via odom");
+            assert_same(
+                &tf2.lookup_transform_full("laser", target_time, "camera", source_time, "odom"),
+                &native.lookup_transform_full("laser", target_time, "camera", source_time, "odom"),
+                &label,
+            );
+        }
+
+        let mut tf2_names = tf2.frame_names().unwrap();
+        let mut native_names = native.frame_names();
+        tf2_names.sort();
+        native_names.sort();
+        assert_eq!(tf2_names, native_names);
+        for frame in frames {
+            assert_eq!(
+                tf2.parent_frame(frame, LookupTime::Latest).unwrap(),
+                native.parent_frame(frame, LookupTime::Latest),
+                "parent of {frame}"
+            );
+        }
+    }
+
+    #[test]
+    fn chains_match_tf2() {
+        let tf2 = crate::backend::Backend::new(Duration::from_secs(10));
+        let mut native = FrameTree::new(Duration::from_secs(10));
+
+        let inserts = [
+            (edge("map", "odom", (1, 0), [1.0, 2.0, 0.0], 0.3), false),
+            (edge("map", "odom", (3, 0), [2.0, 2.0, 0.0], 0.6), false),
+            (
+                edge("odom", "base_link", (2, 0), [0.5, 0.0, 0.0], 1.0),
+                false,
+            ),
+            (
+                edge("odom", "base_link", (4, 0), [1.5, 0.5, 0.0], -1.0),
+                false,
+            ),
+            (
+                edge("base_link", "arm", (2, 0), [0.1, 0.0, 0.4], 0.5),
+                false,
+            ),
+            (
+                edge("base_link", "laser", (0, 0), [0.2, 0.0, 0.3], 3.0),
+                true,
+            ),
+            (edge("arm", "camera", (0, 0), [-0.1, 0.1, 0.5], -2.0), true),
+            (
+                edge("world", "island", (2, 500_000_000), [0.0, 0.0, 1.0], 0.0),
+                false,
+            ),
+        ];
+        for (tf, is_static) in &inserts {
+            tf2.set_transform(tf, "diff", *is_static).unwrap();
+            native.set_transform(tf, "diff", *is_static).unwrap();
+        }
+
+        let frames = [
+            "map",
+            "odom",
+            "base_link",
+            "arm",
+            "laser",
+            "camera",
+            "island",
+            "ghost",
+        ];
+        let times = [
+            LookupTime::Latest,
+            LookupTime::Time { sec: 1, nanosec: 0 },
+            LookupTime::Time {
+                sec: 2,
+                nanosec: 500_000_000,
+            },
+            LookupTime::Time { sec: 3, nanosec: 0 },
+            LookupTime::Time { sec: 9, nanosec: 0 },
+        ];
+
+        for target in frames {
+            for source in frames {
+                for fixed in [target, "odom", "base_link"] {
+                    for target_time in times {
+                        for source_time in times {
+                            let label = format!(
+                                "{target}@{target_time:?} <- {source}@{source_time:?} via {fixed}"
+                            );
+                            let a = tf2.chain_as_vector(
+                                target,
+                                target_time,
+                                source,
+                                source_time,
+                                fixed,
+                            );
+                            let b = native.chain_as_vector(
+                                target,
+                                target_time,
+                                source,
+                                source_time,
+                                fixed,
+                            );
+                            match (a, b) {
+                                (Ok(a), Ok(b)) => assert_eq!(a, b, "{label}"),
+                                (Err(a), Err(b)) => assert_eq!(
+                                    std::mem::discriminant(&a),
+                                    std::mem::discriminant(&b),
+                                    "{label}: {a} vs {b}"
+                                ),
+                                (a, b) => panic!("{label}: tf2 gave {a:?}, native gave {b:?}"),
+                            }
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    /// Pins the parts of tf2's extrapolation messages the bridge parses:
+    /// exact requested stamps survive, and the earliest/latest markers give
+    /// the buffered range.
+    #[test]
+    fn extrapolation_errors_match_tf2() {
+        let mut tf2 = BufferCore::new(Duration::from_secs(10));
+        let mut native = FrameTree::new(Duration::from_secs(10));
+        for tf in [
+            edge("map", "odom", (1, 0), [0.0; 3], 0.0),
+            edge("map", "odom", (2, 0), [0.0; 3], 0.0),
+            edge("odom", "base_link", (5, 0), [0.0; 3], 0.0),
+            edge("odom", "base_link", (6, 0), [0.0; 3], 0.0),
+        ] {
+            tf2.set_transform(&tf, "diff", false).unwrap();
+            native.set_transform(&tf, "diff", false).unwrap();
+        }
+
+        let at = |sec, nanosec| LookupTime::Time { sec, nanosec };
+        // (source, when, requested, earliest, latest). Latest resolves to
+        // the newest common time (2 s), which only the message reports.
+        let cases = [
+            (
+                "odom",
+                at(0, 123_456_789),
+                at(0, 123_456_789),
+                Some(at(1, 0)),
+                None,
+            ),
+            (
+                "odom",
+                at(3, 123_456_789),
+                at(3, 123_456_789),
+                None,
+                Some(at(2, 0)),
+            ),
+            (
+                "base_link",
+                LookupTime::Latest,
+                at(2, 0),
+                Some(at(5, 0)),
+                None,
+            ),
+        ];
+        for (source, when, requested, earliest, latest) in cases {
+            let label = format!("map <- {source} at {when:?}");
+            let a = tf2.lookup_transform("map", source, when);
+            match &a {
+                Err(Tf2Error::Extrapolation {
+                    requested: r,
+                    available,
+                    ..
+                }) => {
+                    assert_eq!(*r, requested, "{label}: requested");
+                    assert_eq!(available.earliest, earliest, "{label}: earliest");
+                    assert_eq!(available.latest, latest, "{label}: latest");
+                }
+                other => panic!("{label}: expected an extrapolation error, got {other:?}"),
+            }
+            assert_same(&a, &native.lookup_transform("map", source, when), &label);
+        }
+    }
+}
@@ -0,0 +1,93 @@
+use std::collections::HashMap;
+use std::sync::{Condvar, Mutex, MutexGuard, PoisonError};
+use std::task::Waker;
+use std::time::Instant;
+
+#[derive(Default)]
+struct NotifyState {
+    generation: u64,
+    next_waiter: u64,
+    wakers: HashMap<u64, Waker>,
+}
+
+/// Wakes threads and tasks waiting on a `BufferCore` whenever new transform
+/// data is inserted. Waiters compare against a generation counter so a
+/// notification that lands between a failed lookup and the wait is never lost.
+#[derive(Default)]
+pub(crate) struct TransformNotifier {
+    state: Mutex<NotifyState>,
+    cond: Condvar,
+}
+
+impl TransformNotifier {
+    fn lock(&self) -> MutexGuard<'_, NotifyState> {
+        self.state.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    pub fn generation(&self) -> u64 {
+        self.lock().generation
+    }
+
+    pub fn notify(&self) {
+        let wakers = {
+            let mut state = self.lock();
+            state.generation = state.generation.wrapping_add(1);
+            std::mem::take(&mut state.wakers)
+        };
+        self.cond.notify_all();
+        for waker in wakers.into_values() {
+            waker.wake();
+        }
+    }
+
+    /// Blocks until the generation moves past `seen`. Returns `false` if
+    /// `deadline` passes first; `None` waits without a deadline.
+    pub fn wait_past(&self, seen: u64, deadline: Option<Instant>) -> bool {
+        let mut state = self.lock();
+        while state.generation == seen {
+            state = match deadline {
+                Some(deadline) => {
+                    let now = Instant::now();
+                    if now >= deadline {
+                        return false;
+                    }
+                    self.cond
+                        .wait_timeout(state, deadline - now)
+                        .unwrap_or_else(PoisonError::into_inner)
+                        .0
+                }
+                None => self
+                    .cond
+                    .wait(state)
+                    .unwrap_or_else(PoisonError::into_inner),
+            };
+        }
+        true
+    }
+
+    /// Registers `waker` to be woken on the next notification, reusing the
+    /// slot in `waiter` if the task registered before. Returns `false`
+    /// without registering if the generation already moved past `seen`, in
+    /// which case the caller should retry right away.
+    pub fn register_waker(&self, seen: u64, waiter: &mut Option<u64>, waker: &Waker) -> bool {
+        let mut state = self.lock();
+        if state.generation != seen {
+            return false;
+        }
+        let id = *waiter.get_or_insert_with(|| {
+            state.next_waiter = state.next_waiter.wrapping_add(1);
+            state.next_waiter
+        });
+        state.wakers.insert(id, waker.clone());
+        true
+    }
+
+    pub fn remove_waker(&self, id: u64) {
+        self.lock().wakers.remove(&id);
+    }
+
+    #[cfg(test)]
+    pub fn waker_count(&self) -> usize {
+        self.lock().wakers.len()
+    }
+}
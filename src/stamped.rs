@@ -0,0 +1,127 @@
+use crate::error::Tf2Error;
+use crate::transform::{HasHeader, Transformable, TransformablePayload};
+use crate::transform_stamped::TransformStamped;
+
+/// Any value tagged with the frame it is expressed in and a stamp, for data
+/// that is not a ROS message (detections, grasp candidates, ...).
+///
+/// `Stamped<T>` is [`Transformable`] when `T` is [`TransformablePayload`], so
+/// it works with [`BufferCore::transform`](crate::BufferCore::transform).
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Stamped<T> {
+    pub frame_id: String,
+    pub stamp_sec: i32,
+    pub stamp_nanosec: u32,
+    pub payload: T,
+}
+
+impl<T> Stamped<T> {
+    pub fn new(frame_id: impl Into<String>, stamp: (i32, u32), payload: T) -> Self {
+        Self {
+            frame_id: frame_id.into(),
+            stamp_sec: stamp.0,
+            stamp_nanosec: stamp.1,
+            payload,
+        }
+    }
+}
+
+impl<T> HasHeader for Stamped<T> {
+    fn frame_id(&self) -> &str {
+        &self.frame_id
+    }
+
+    fn stamp(&self) -> (i32, u32) {
+        (self.stamp_sec, self.stamp_nanosec)
+    }
+}
+
+/// Like tf2's `doTransform`, the result takes the frame and stamp of `tf`.
+impl<T: TransformablePayload> Transformable for Stamped<T> {
+    fn apply_transform(&self, tf: &TransformStamped) -> Result<Self, Tf2Error> {
+        Ok(Self {
+            frame_id: tf.parent_frame.clone(),
+            stamp_sec: tf.stamp_sec,
+            stamp_nanosec: tf.stamp_nanosec,
+            payload: self.payload.transform_payload(tf)?,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{BufferCore, TimeSpec};
+    use geometry_msgs::msg::Point;
+    use std::time::Duration;
+
+    #[derive(Clone, Debug, PartialEq)]
+    struct Detection {
+        center: [f64; 3],
+        score: f32,
+    }
+
+    impl TransformablePayload for Detection {
+        fn transform_payload(&self, tf: &TransformStamped) -> Result<Self, Tf2Error> {
+            Ok(Self {
+                center: tf.transform_point(self.center),
+                score: self.score,
+            })
+        }
+    }
+
+    fn buffer_with_map_odom() -> BufferCore {
+        let mut buffer = BufferCore::new(Duration::from_secs(10));
+        let mut tf = TransformStamped::from_euler(
+            "map",
+            "odom",
+            [1.0, 2.0, 0.0],
+            [0.0, 0.0, std::f64::consts::FRAC_PI_2],
+        );
+        tf.stamp_sec = 5;
+        buffer.set_transform(&tf, "test", false).unwrap();
+        buffer
+    }
+
+    #[test]
+    fn buffer_transforms_stamped_point() {
+        let buffer = buffer_with_map_odom();
+        let point = Stamped::new(
+            "odom",
+            (5, 0),
+            Point {
+                x: 1.0,
+                y: 0.0,
+                z: 3.0,
+            },
+        );
+
+        let out = buffer.transform(&point, "map", TimeSpec::FromMsg).unwrap();
+        assert_eq!(out.frame_id, "map");
+        assert_eq!((out.stamp_sec, out.stamp_nanosec), (5, 0));
+        assert!((out.payload.x - 1.0).abs() < 1e-9);
+        assert!((out.payload.y - 3.0).abs() < 1e-9);
+        assert!((out.payload.z - 3.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn buffer_transforms_custom_payload() {
+        let buffer = buffer_with_map_odom();
+        let detection = Stamped::new(
+            "odom",
+            (5, 0),
+            Detection {
+                center: [0.0, 1.0, 0.0],
+                score: 0.9,
+            },
+        );
+
+        let out = buffer
+            .transform(&detection, "map", TimeSpec::Latest)
+            .unwrap();
+        assert_eq!(out.frame_id, "map");
+        assert_eq!(out.payload.score, 0.9);
+        assert!((out.payload.center[0] - 0.0).abs() < 1e-9);
+        assert!((out.payload.center[1] - 2.0).abs() < 1e-9);
+    }
+}
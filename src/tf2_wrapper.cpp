@@ -2,17 +2,35 @@
 
 #include "tf2_rs/src/ffi.rs.h"
 
+#include <algorithm>
 #include <chrono>
+#include <cmath>
+#include <cstdlib>
+#include <cstring>
+#include <initializer_list>
 #include <string>
+#include <vector>
 
 #if __has_include(<geometry_msgs/msg/transform_stamped.hpp>)
   #include <geometry_msgs/msg/transform_stamped.hpp>
   #include <geometry_msgs/msg/point_stamped.hpp>
   #include <geometry_msgs/msg/pose_stamped.hpp>
+  #include <geometry_msgs/msg/vector3_stamped.hpp>
+  #include <geometry_msgs/msg/quaternion_stamped.hpp>
+  #include <geometry_msgs/msg/pose_with_covariance_stamped.hpp>
+  #include <geometry_msgs/msg/twist_stamped.hpp>
+  #include <geometry_msgs/msg/wrench_stamped.hpp>
+  #include <geometry_msgs/msg/polygon_stamped.hpp>
 #else
   #include <geometry_msgs/geometry_msgs/msg/transform_stamped.hpp>
   #include <geometry_msgs/geometry_msgs/msg/point_stamped.hpp>
   #include <geometry_msgs/geometry_msgs/msg/pose_stamped.hpp>
+  #include <geometry_msgs/geometry_msgs/msg/vector3_stamped.hpp>
+  #include <geometry_msgs/geometry_msgs/msg/quaternion_stamped.hpp>
+  #include <geometry_msgs/geometry_msgs/msg/pose_with_covariance_stamped.hpp>
+  #include <geometry_msgs/geometry_msgs/msg/twist_stamped.hpp>
+  #include <geometry_msgs/geometry_msgs/msg/wrench_stamped.hpp>
+  #include <geometry_msgs/geometry_msgs/msg/polygon_stamped.hpp>
 #endif
 
 #if __has_include(<std_msgs/msg/header.hpp>)
@@ -70,6 +88,67 @@ static Tf2Status with_tf2_status(Fn&& fn) {
 }
 
 
+// Reads the seconds tf2 prints right after `marker` in an exception message.
+static bool parse_time_after(const std::string& what, const char* marker, Tf2Time& out) {
+  const auto pos = what.find(marker);
+  if (pos == std::string::npos) {
+    return false;
+  }
+  const char* start = what.c_str() + pos + std::strlen(marker);
+  char* end = nullptr;
+  const double seconds = std::strtod(start, &end);
+  if (end == start) {
+    return false;
+  }
+  // tf2 prints microseconds; round away the float noise.
+  const auto ns = static_cast<int64_t>(std::llround(seconds * 1e6)) * 1000;
+  out.sec = static_cast<int32_t>(ns / 1000000000);
+  out.nanosec = static_cast<uint32_t>(ns % 1000000000);
+  return true;
+}
+
+// Adds the request to a failed lookup's status: its frames, which of
+// `frames` the buffer has never seen, and for extrapolation the requested
+// time and the buffered bound tf2 reports.
+static Tf2Status with_lookup_context(
+    Tf2Status st,
+    const tf2::BufferCore& buffer,
+    rust::Str target_frame,
+    rust::Str source_frame,
+    const Tf2Time& time,
+    std::initializer_list<rust::Str> frames)
+{
+  if (st.code == Tf2Errc::Ok) {
+    return st;
+  }
+  st.target_frame = rust::String(target_frame);
+  st.source_frame = rust::String(source_frame);
+  st.requested_time = time;
+
+  if (st.code == Tf2Errc::Lookup) {
+    for (const auto& frame : frames) {
+      const std::string id(frame);
+      if (!id.empty() && !buffer._frameExists(id)) {
+        st.missing_frames.push_back(rust::String(id));
+      }
+    }
+  } else if (st.code == Tf2Errc::Extrapolation) {
+    const std::string what(st.message);
+    // tf2 prints times to the microsecond, so only fall back to the message
+    // when the caller asked for "latest" and has no exact time of its own.
+    if (time.sec == 0 && time.nanosec == 0 &&
+        !parse_time_after(what, "Requested time ", st.requested_time)) {
+      parse_time_after(what, "extrapolation at time ", st.requested_time);
+    }
+    if (parse_time_after(what, "but only time ", st.earliest_time)) {
+      st.latest_time = st.earliest_time;
+    }
+    parse_time_after(what, "the earliest data is at time ", st.earliest_time);
+    parse_time_after(what, "the latest data is at time ", st.latest_time);
+  }
+  return st;
+}
+
 static tf2::TimePoint to_timepoint(const Tf2Time& t) {
   // TF2 convention: (0,0) means "latest available"
   if (t.sec == 0 && t.nanosec == 0) {
@@ -78,6 +157,15 @@ static tf2::TimePoint to_timepoint(const Tf2Time& t) {
   return tf2::TimePoint(std::chrono::seconds(t.sec) + std::chrono::nanoseconds(t.nanosec));
 }
 
+static Tf2Time from_timepoint(const tf2::TimePoint& t) {
+  const auto ns = std::chrono::duration_cast<std::chrono::nanoseconds>(
+      t.time_since_epoch()).count();
+  Tf2Time out;
+  out.sec = static_cast<int32_t>(ns / 1000000000);
+  out.nanosec = static_cast<uint32_t>(ns % 1000000000);
+  return out;
+}
+
 static std_msgs::msg::Header to_ros_header(const Tf2Header& h) {
   std_msgs::msg::Header out;
   out.stamp.sec = h.stamp.sec;
@@ -184,7 +272,7 @@ Tf2Status BufferCoreWrapper::lookup_transform(
     const Tf2Time& time,
     Tf2TransformStamped& out_tf) const
 {
-  return with_tf2_status([&] {
+  auto st = with_tf2_status([&] {
     const auto tp = to_timepoint(time);
     auto tf = buffer_.lookupTransform(
         std::string(target_frame),
@@ -192,6 +280,162 @@ Tf2Status BufferCoreWrapper::lookup_transform(
         tp);
     out_tf = from_ros(tf);
   });
+  return with_lookup_context(
+      std::move(st), buffer_, target_frame, source_frame, time,
+      {target_frame, source_frame});
+}
+
+Tf2Status BufferCoreWrapper::can_transform_full(
+    rust::Str target_frame,
+    const Tf2Time& target_time,
+    rust::Str source_frame,
+    const Tf2Time& source_time,
+    rust::Str fixed_frame,
+    Tf2CanTransformResult& out) const
+{
+  out.available = false;
+  out.diagnostic = "";
+
+  return with_tf2_status([&] {
+    std::string err;
+    out.available = buffer_.canTransform(
+        std::string(target_frame),
+        to_timepoint(target_time),
+        std::string(source_frame),
+        to_timepoint(source_time),
+        std::string(fixed_frame),
+        &err);
+
+    if (!out.available) {
+      out.diagnostic = std::move(err);
+    }
+  });
+}
+
+Tf2Status BufferCoreWrapper::lookup_transform_full(
+    rust::Str target_frame,
+    const Tf2Time& target_time,
+    rust::Str source_frame,
+    const Tf2Time& source_time,
+    rust::Str fixed_frame,
+    Tf2TransformStamped& out_tf) const
+{
+  auto st = with_tf2_status([&] {
+    auto tf = buffer_.lookupTransform(
+        std::string(target_frame),
+        to_timepoint(target_time),
+        std::string(source_frame),
+        to_timepoint(source_time),
+        std::string(fixed_frame));
+    out_tf = from_ros(tf);
+  });
+  return with_lookup_context(
+      std::move(st), buffer_, target_frame, source_frame, target_time,
+      {target_frame, source_frame, fixed_frame});
+}
+
+Tf2Status BufferCoreWrapper::frame_names(rust::Vec<rust::String>& out) const
+{
+  return with_tf2_status([&] {
+    std::vector<std::string> ids;
+    buffer_._getFrameStrings(ids);
+
+    out.clear();
+    out.reserve(ids.size());
+    for (const auto& id : ids) {
+      out.push_back(rust::String(id));
+    }
+  });
+}
+
+bool BufferCoreWrapper::frame_exists(rust::Str frame_id) const
+{
+  return buffer_._frameExists(std::string(frame_id));
+}
+
+Tf2Status BufferCoreWrapper::get_parent(
+    rust::Str frame_id,
+    const Tf2Time& time,
+    Tf2ParentResult& out) const
+{
+  out.has_parent = false;
+  out.parent_frame = "";
+
+  return with_tf2_status([&] {
+    std::string parent;
+    out.has_parent = buffer_._getParent(std::string(frame_id), to_timepoint(time), parent);
+    if (out.has_parent) {
+      out.parent_frame = parent;
+    }
+  });
+}
+
+Tf2Status BufferCoreWrapper::latest_common_time(
+    rust::Str target_frame,
+    rust::Str source_frame,
+    Tf2Time& out) const
+{
+  out = Tf2Time{};
+
+  std::string err;
+  tf2::TF2Error code{};
+  tf2::TimePoint time;
+  auto st = with_tf2_status([&] {
+    const auto target_id = buffer_._lookupFrameNumber(std::string(target_frame));
+    const auto source_id = buffer_._lookupFrameNumber(std::string(source_frame));
+    code = buffer_._getLatestCommonTime(target_id, source_id, time, &err);
+  });
+  if (st.code == Tf2Errc::Ok && code != tf2::TF2Error{}) {
+    // tf2::TF2Error numbers its lookup errors like Tf2Errc; only the
+    // enumerator names differ between distros.
+    const auto value = static_cast<int32_t>(code);
+    st = status(value <= 4 ? static_cast<Tf2Errc>(value) : Tf2Errc::Other, err);
+  }
+  if (st.code == Tf2Errc::Ok) {
+    out = from_timepoint(time);
+  }
+  return with_lookup_context(
+      std::move(st), buffer_, target_frame, source_frame, Tf2Time{},
+      {target_frame, source_frame});
+}
+
+Tf2Status BufferCoreWrapper::chain_as_vector(
+    rust::Str target_frame,
+    const Tf2Time& target_time,
+    rust::Str source_frame,
+    const Tf2Time& source_time,
+    rust::Str fixed_frame,
+    rust::Vec<rust::String>& out) const
+{
+  auto st = with_tf2_status([&] {
+    std::vector<std::string> chain;
+    buffer_._chainAsVector(
+        std::string(target_frame),
+        to_timepoint(target_time),
+        std::string(source_frame),
+        to_timepoint(source_time),
+        std::string(fixed_frame),
+        chain);
+
+    out.clear();
+    out.reserve(chain.size());
+    for (const auto& id : chain) {
+      out.push_back(rust::String(id));
+    }
+  });
+  return with_lookup_context(
+      std::move(st), buffer_, target_frame, source_frame, target_time,
+      {target_frame, source_frame, fixed_frame});
+}
+
+rust::String BufferCoreWrapper::all_frames_as_yaml() const
+{
+  return rust::String(buffer_.allFramesAsYAML());
+}
+
+rust::String BufferCoreWrapper::all_frames_as_string() const
+{
+  return rust::String(buffer_.allFramesAsString());
 }
 
 std::shared_ptr<BufferCoreWrapper> new_buffer_core(uint64_t cache_time_ns) {
@@ -272,6 +516,255 @@ Tf2Status do_transform_pose_stamped(
   });
 }
 
+// geometry_msgs/Vector3Stamped
+static geometry_msgs::msg::Vector3Stamped to_ros(const Tf2Vector3Stamped& v) {
+  geometry_msgs::msg::Vector3Stamped out;
+  out.header = to_ros_header(v.header);
+  out.vector.x = v.x;
+  out.vector.y = v.y;
+  out.vector.z = v.z;
+  return out;
+}
+
+static Tf2Vector3Stamped from_ros(const geometry_msgs::msg::Vector3Stamped& v) {
+  Tf2Vector3Stamped out;
+  out.header = from_ros_header(v.header);
+  out.x = v.vector.x;
+  out.y = v.vector.y;
+  out.z = v.vector.z;
+  return out;
+}
+
+Tf2Status do_transform_vector3_stamped(
+    const Tf2Vector3Stamped& input,
+    const Tf2TransformStamped& tf,
+    Tf2Vector3Stamped& out) {
+  return with_tf2_status([&] {
+    const auto in_ros = to_ros(input);
+    const auto tf_ros = to_ros(tf);
+    geometry_msgs::msg::Vector3Stamped out_ros;
+    tf2::doTransform(in_ros, out_ros, tf_ros);
+    out = from_ros(out_ros);
+  });
+}
+
+// geometry_msgs/QuaternionStamped
+static geometry_msgs::msg::QuaternionStamped to_ros(const Tf2QuaternionStamped& v) {
+  geometry_msgs::msg::QuaternionStamped out;
+  out.header = to_ros_header(v.header);
+  out.quaternion.x = v.x;
+  out.quaternion.y = v.y;
+  out.quaternion.z = v.z;
+  out.quaternion.w = v.w;
+  return out;
+}
+
+static Tf2QuaternionStamped from_ros(const geometry_msgs::msg::QuaternionStamped& v) {
+  Tf2QuaternionStamped out;
+  out.header = from_ros_header(v.header);
+  out.x = v.quaternion.x;
+  out.y = v.quaternion.y;
+  out.z = v.quaternion.z;
+  out.w = v.quaternion.w;
+  return out;
+}
+
+Tf2Status do_transform_quaternion_stamped(
+    const Tf2QuaternionStamped& input,
+    const Tf2TransformStamped& tf,
+    Tf2QuaternionStamped& out) {
+  return with_tf2_status([&] {
+    const auto in_ros = to_ros(input);
+    const auto tf_ros = to_ros(tf);
+    geometry_msgs::msg::QuaternionStamped out_ros;
+    tf2::doTransform(in_ros, out_ros, tf_ros);
+    out = from_ros(out_ros);
+  });
+}
+
+// geometry_msgs/TransformStamped (composition: out = tf * input)
+Tf2Status do_transform_transform_stamped(
+    const Tf2TransformStamped& input,
+    const Tf2TransformStamped& tf,
+    Tf2TransformStamped& out) {
+  return with_tf2_status([&] {
+    const auto in_ros = to_ros(input);
+    const auto tf_ros = to_ros(tf);
+    geometry_msgs::msg::TransformStamped out_ros;
+    tf2::doTransform(in_ros, out_ros, tf_ros);
+    out = from_ros(out_ros);
+  });
+}
+
+// geometry_msgs/PoseWithCovarianceStamped
+static geometry_msgs::msg::PoseWithCovarianceStamped to_ros(const Tf2PoseWithCovarianceStamped& v) {
+  if (v.covariance.size() != 36) {
+    throw tf2::InvalidArgumentException("PoseWithCovarianceStamped covariance must have 36 elements");
+  }
+  geometry_msgs::msg::PoseWithCovarianceStamped out;
+  out.header = to_ros_header(v.header);
+  out.pose.pose.position.x = v.position_x;
+  out.pose.pose.position.y = v.position_y;
+  out.pose.pose.position.z = v.position_z;
+  out.pose.pose.orientation.x = v.orientation_x;
+  out.pose.pose.orientation.y = v.orientation_y;
+  out.pose.pose.orientation.z = v.orientation_z;
+  out.pose.pose.orientation.w = v.orientation_w;
+  std::copy(v.covariance.begin(), v.covariance.end(), out.pose.covariance.begin());
+  return out;
+}
+
+static Tf2PoseWithCovarianceStamped from_ros(const geometry_msgs::msg::PoseWithCovarianceStamped& v) {
+  Tf2PoseWithCovarianceStamped out;
+  out.header = from_ros_header(v.header);
+  out.position_x = v.pose.pose.position.x;
+  out.position_y = v.pose.pose.position.y;
+  out.position_z = v.pose.pose.position.z;
+  out.orientation_x = v.pose.pose.orientation.x;
+  out.orientation_y = v.pose.pose.orientation.y;
+  out.orientation_z = v.pose.pose.orientation.z;
+  out.orientation_w = v.pose.pose.orientation.w;
+  rust::Vec<double> covariance;
+  covariance.reserve(v.pose.covariance.size());
+  for (auto c : v.pose.covariance) {
+    covariance.push_back(c);
+  }
+  out.covariance = std::move(covariance);
+  return out;
+}
+
+Tf2Status do_transform_pose_with_covariance_stamped(
+    const Tf2PoseWithCovarianceStamped& input,
+    const Tf2TransformStamped& tf,
+    Tf2PoseWithCovarianceStamped& out) {
+  return with_tf2_status([&] {
+    const auto in_ros = to_ros(input);
+    const auto tf_ros = to_ros(tf);
+    geometry_msgs::msg::PoseWithCovarianceStamped out_ros;
+    tf2::doTransform(in_ros, out_ros, tf_ros);
+    out = from_ros(out_ros);
+  });
+}
+
+// geometry_msgs/TwistStamped
+// Not every distro ships a tf2 doTransform for twists, so both vectors are
+// rotated the same way tf2 handles wrenches (no lever-arm term).
+Tf2Status do_transform_twist_stamped(
+    const Tf2TwistStamped& input,
+    const Tf2TransformStamped& tf,
+    Tf2TwistStamped& out) {
+  return with_tf2_status([&] {
+    const auto tf_ros = to_ros(tf);
+
+    geometry_msgs::msg::Vector3Stamped linear_in;
+    linear_in.header = to_ros_header(input.header);
+    linear_in.vector.x = input.linear_x;
+    linear_in.vector.y = input.linear_y;
+    linear_in.vector.z = input.linear_z;
+
+    geometry_msgs::msg::Vector3Stamped angular_in;
+    angular_in.header = linear_in.header;
+    angular_in.vector.x = input.angular_x;
+    angular_in.vector.y = input.angular_y;
+    angular_in.vector.z = input.angular_z;
+
+    geometry_msgs::msg::Vector3Stamped linear_out;
+    geometry_msgs::msg::Vector3Stamped angular_out;
+    tf2::doTransform(linear_in, linear_out, tf_ros);
+    tf2::doTransform(angular_in, angular_out, tf_ros);
+
+    out.header = from_ros_header(linear_out.header);
+    out.linear_x = linear_out.vector.x;
+    out.linear_y = linear_out.vector.y;
+    out.linear_z = linear_out.vector.z;
+    out.angular_x = angular_out.vector.x;
+    out.angular_y = angular_out.vector.y;
+    out.angular_z = angular_out.vector.z;
+  });
+}
+
+// geometry_msgs/WrenchStamped
+static geometry_msgs::msg::WrenchStamped to_ros(const Tf2WrenchStamped& v) {
+  geometry_msgs::msg::WrenchStamped out;
+  out.header = to_ros_header(v.header);
+  out.wrench.force.x = v.force_x;
+  out.wrench.force.y = v.force_y;
+  out.wrench.force.z = v.force_z;
+  out.wrench.torque.x = v.torque_x;
+  out.wrench.torque.y = v.torque_y;
+  out.wrench.torque.z = v.torque_z;
+  return out;
+}
+
+static Tf2WrenchStamped from_ros(const geometry_msgs::msg::WrenchStamped& v) {
+  Tf2WrenchStamped out;
+  out.header = from_ros_header(v.header);
+  out.force_x = v.wrench.force.x;
+  out.force_y = v.wrench.force.y;
+  out.force_z = v.wrench.force.z;
+  out.torque_x = v.wrench.torque.x;
+  out.torque_y = v.wrench.torque.y;
+  out.torque_z = v.wrench.torque.z;
+  return out;
+}
+
+Tf2Status do_transform_wrench_stamped(
+    const Tf2WrenchStamped& input,
+    const Tf2TransformStamped& tf,
+    Tf2WrenchStamped& out) {
+  return with_tf2_status([&] {
+    const auto in_ros = to_ros(input);
+    const auto tf_ros = to_ros(tf);
+    geometry_msgs::msg::WrenchStamped out_ros;
+    tf2::doTransform(in_ros, out_ros, tf_ros);
+    out = from_ros(out_ros);
+  });
+}
+
+// geometry_msgs/PolygonStamped
+static geometry_msgs::msg::PolygonStamped to_ros(const Tf2PolygonStamped& v) {
+  geometry_msgs::msg::PolygonStamped out;
+  out.header = to_ros_header(v.header);
+  out.polygon.points.reserve(v.points.size());
+  for (const auto& p : v.points) {
+    geometry_msgs::msg::Point32 pt;
+    pt.x = p.x;
+    pt.y = p.y;
+    pt.z = p.z;
+    out.polygon.points.push_back(pt);
+  }
+  return out;
+}
+
+static Tf2PolygonStamped from_ros(const geometry_msgs::msg::PolygonStamped& v) {
+  Tf2PolygonStamped out;
+  out.header = from_ros_header(v.header);
+  rust::Vec<Tf2Point32> points;
+  points.reserve(v.polygon.points.size());
+  for (const auto& p : v.polygon.points) {
+    Tf2Point32 pt;
+    pt.x = p.x;
+    pt.y = p.y;
+    pt.z = p.z;
+    points.push_back(pt);
+  }
+  out.points = std::move(points);
+  return out;
+}
+
+Tf2Status do_transform_polygon_stamped(
+    const Tf2PolygonStamped& input,
+    const Tf2TransformStamped& tf,
+    Tf2PolygonStamped& out) {
+  return with_tf2_status([&] {
+    const auto in_ros = to_ros(input);
+    const auto tf_ros = to_ros(tf);
+    geometry_msgs::msg::PolygonStamped out_ros;
+    tf2::doTransform(in_ros, out_ros, tf_ros);
+    out = from_ros(out_ros);
+  });
+}
+
 // sensor_msgs/PointCloud2
 static sensor_msgs::msg::PointCloud2 to_ros_pc2(const Tf2PointCloud2& in) {
   sensor_msgs::msg::PointCloud2 out;
@@ -1,3 +1,4 @@
+#[cfg(feature = "tf2-cpp")]
 use crate::ffi::ffi;
 use crate::transform::HasHeader;
 
@@ -21,7 +22,7 @@ impl TimeSpec {
     }
 }
 
-#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
 pub enum LookupTime {
     Latest,
     Time { sec: i32, nanosec: u32 },
@@ -32,8 +33,24 @@ impl LookupTime {
         let (sec, nanosec) = msg.stamp();
         LookupTime::Time { sec, nanosec }
     }
+
+    /// Nanoseconds since the epoch, or `None` for `Latest`.
+    pub(crate) fn as_nanos(self) -> Option<i64> {
+        match self {
+            LookupTime::Latest => None,
+            LookupTime::Time { sec, nanosec } => Some(sec as i64 * 1_000_000_000 + nanosec as i64),
+        }
+    }
+
+    pub(crate) fn from_nanos(ns: i64) -> Self {
+        LookupTime::Time {
+            sec: ns.div_euclid(1_000_000_000) as i32,
+            nanosec: ns.rem_euclid(1_000_000_000) as u32,
+        }
+    }
 }
 
+#[cfg(feature = "tf2-cpp")]
 impl From<LookupTime> for ffi::Tf2Time {
     fn from(v: LookupTime) -> Self {
         match v {
@@ -42,3 +59,13 @@ impl From<LookupTime> for ffi::Tf2Time {
         }
     }
 }
+
+#[cfg(feature = "tf2-cpp")]
+impl From<ffi::Tf2Time> for LookupTime {
+    fn from(v: ffi::Tf2Time) -> Self {
+        match (v.sec, v.nanosec) {
+            (0, 0) => LookupTime::Latest,
+            (sec, nanosec) => LookupTime::Time { sec, nanosec },
+        }
+    }
+}
@@ -0,0 +1,208 @@
+//! Messages made of several poses, transformed on the Rust side with the
+//! per-pose [`TransformablePayload`] impls.
+
+use crate::Tf2Error;
+use crate::buffer::BufferCore;
+use crate::time::TimeSpec;
+use crate::transform::{HasHeader, Transformable, TransformablePayload, restamp};
+use crate::transform_stamped::TransformStamped;
+
+impl Transformable for geometry_msgs::msg::PoseArray {
+    fn apply_transform(&self, tf: &TransformStamped) -> Result<Self, Tf2Error> {
+        let mut out = self.clone();
+        restamp(&mut out.header, tf);
+        out.poses = self.poses.transform_payload(tf)?;
+        Ok(out)
+    }
+}
+
+/// Every pose is taken to be in the path's frame, as nav2 does. Each keeps
+/// its own stamp.
+impl Transformable for nav_msgs::msg::Path {
+    fn apply_transform(&self, tf: &TransformStamped) -> Result<Self, Tf2Error> {
+        let mut out = self.clone();
+        restamp(&mut out.header, tf);
+        for pose in &mut out.poses {
+            pose.header.frame_id = tf.parent_frame.clone();
+            pose.pose = pose.pose.transform_payload(tf)?;
+        }
+        Ok(out)
+    }
+}
+
+/// Only the pose moves: `points`, `scale` and the mesh are relative to it.
+impl Transformable for visualization_msgs::msg::Marker {
+    fn apply_transform(&self, tf: &TransformStamped) -> Result<Self, Tf2Error> {
+        let mut out = self.clone();
+        restamp(&mut out.header, tf);
+        out.pose = self.pose.transform_payload(tf)?;
+        Ok(out)
+    }
+}
+
+/// A `MarkerArray` has no header of its own; it reports its first marker's,
+/// and an empty frame when there are no markers. Prefer
+/// [`BufferCore::transform_markers`], which accepts empty arrays.
+impl HasHeader for visualization_msgs::msg::MarkerArray {
+    fn frame_id(&self) -> &str {
+        self.markers
+            .first()
+            .map_or("", |m| m.header.frame_id.as_str())
+    }
+
+    fn stamp(&self) -> (i32, u32) {
+        self.markers
+            .first()
+            .map_or((0, 0), |m| (m.header.stamp.sec, m.header.stamp.nanosec))
+    }
+}
+
+/// All markers must share one frame, since they get the same transform. For
+/// mixed frames, use [`BufferCore::transform_markers`].
+impl Transformable for visualization_msgs::msg::MarkerArray {
+    fn apply_transform(&self, tf: &TransformStamped) -> Result<Self, Tf2Error> {
+        let frame_id = self.frame_id();
+        if let Some(other) = self.markers.iter().find(|m| m.header.frame_id != frame_id) {
+            return Err(Tf2Error::InvalidArgument(format!(
+                "MarkerArray mixes frames {frame_id} and {}; transform its markers one by one",
+                other.header.frame_id
+            )));
+        }
+        Ok(Self {
+            markers: self
+                .markers
+                .iter()
+                .map(|m| m.apply_transform(tf))
+                .collect::<Result<_, _>>()?,
+        })
+    }
+}
+
+impl BufferCore {
+    /// Transforms every marker of `array` into `target_frame`, looking up
+    /// each (frame, stamp) pair once. Unlike [`BufferCore::transform`], an
+    /// empty array is returned as is and markers may be in different frames.
+    pub fn transform_markers(
+        &self,
+        array: &visualization_msgs::msg::MarkerArray,
+        target_frame: &str,
+        time: TimeSpec,
+    ) -> Result<visualization_msgs::msg::MarkerArray, Tf2Error> {
+        Ok(visualization_msgs::msg::MarkerArray {
+            markers: self.transform_many(&array.markers, target_frame, time)?,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use geometry_msgs::msg::Pose;
+
+    fn map_odom() -> TransformStamped {
+        let mut tf = TransformStamped::from_euler(
+            "map",
+            "odom",
+            [1.0, 0.0, 0.0],
+            [0.0, 0.0, std::f64::consts::FRAC_PI_2],
+        );
+        tf.stamp_sec = 4;
+        tf
+    }
+
+    fn pose_at(x: f64, y: f64) -> Pose {
+        let mut pose = Pose::default();
+        pose.position.x = x;
+        pose.position.y = y;
+        pose.orientation.w = 1.0;
+        pose
+    }
+
+    fn assert_position(pose: &Pose, expected: [f64; 2]) {
+        assert!((pose.position.x - expected[0]).abs() < 1e-9, "{pose:?}");
+        assert!((pose.position.y - expected[1]).abs() < 1e-9, "{pose:?}");
+    }
+
+    #[test]
+    fn pose_array_moves_every_pose() {
+        let mut msg = geometry_msgs::msg::PoseArray::default();
+        msg.header.frame_id = "odom".to_string();
+        msg.poses = vec![pose_at(1.0, 0.0), pose_at(0.0, 1.0)];
+
+        let out = msg.apply_transform(&map_odom()).unwrap();
+        assert_eq!(out.header.frame_id, "map");
+        assert_eq!(out.header.stamp.sec, 4);
+        assert_position(&out.poses[0], [1.0, 1.0]);
+        assert_position(&out.poses[1], [0.0, 0.0]);
+        let yaw = 2.0 * out.poses[0].orientation.z.atan2(out.poses[0].orientation.w);
+        assert!((yaw - std::f64::consts::FRAC_PI_2).abs() < 1e-9);
+    }
+
+    #[test]
+    fn path_poses_keep_their_stamps() {
+        let mut msg = nav_msgs::msg::Path::default();
+        msg.header.frame_id = "odom".to_string();
+        let mut pose = geometry_msgs::msg::PoseStamped::default();
+        pose.header.frame_id = "odom".to_string();
+        pose.header.stamp.sec = 2;
+        pose.pose = pose_at(1.0, 0.0);
+        msg.poses = vec![pose];
+
+        let out = msg.apply_transform(&map_odom()).unwrap();
+        assert_eq!(out.header.frame_id, "map");
+        assert_eq!(out.poses[0].header.frame_id, "map");
+        assert_eq!(out.poses[0].header.stamp.sec, 2);
+        assert_position(&out.poses[0].pose, [1.0, 1.0]);
+    }
+
+    #[test]
+    fn marker_array_needs_a_single_frame() {
+        let mut marker = visualization_msgs::msg::Marker::default();
+        marker.header.frame_id = "odom".to_string();
+        marker.pose = pose_at(1.0, 0.0);
+        let mut msg = visualization_msgs::msg::MarkerArray {
+            markers: vec![marker.clone(), marker.clone()],
+        };
+        assert_eq!(msg.frame_id(), "odom");
+
+        let out = msg.apply_transform(&map_odom()).unwrap();
+        assert!(out.markers.iter().all(|m| m.header.frame_id == "map"));
+        assert_position(&out.markers[1].pose, [1.0, 1.0]);
+
+        marker.header.frame_id = "base_link".to_string();
+        msg.markers.push(marker);
+        assert!(matches!(
+            msg.apply_transform(&map_odom()),
+            Err(Tf2Error::InvalidArgument(_))
+        ));
+    }
+
+    #[test]
+    fn buffer_transforms_empty_and_mixed_marker_arrays() {
+        let mut buffer = BufferCore::new(std::time::Duration::from_secs(10));
+        buffer.set_transform(&map_odom(), "test", true).unwrap();
+        let mut odom_base = TransformStamped::identity("odom", "base_link");
+        odom_base.translation = [0.0, 1.0, 0.0];
+        buffer.set_transform(&odom_base, "test", true).unwrap();
+
+        let empty = visualization_msgs::msg::MarkerArray::default();
+        let out = buffer
+            .transform_markers(&empty, "map", TimeSpec::Latest)
+            .expect("an empty array needs no lookup");
+        assert!(out.markers.is_empty());
+
+        let mut markers = empty;
+        for frame in ["odom", "base_link"] {
+            let mut marker = visualization_msgs::msg::Marker::default();
+            marker.header.frame_id = frame.to_string();
+            marker.pose = pose_at(1.0, 0.0);
+            markers.markers.push(marker);
+        }
+        let out = buffer
+            .transform_markers(&markers, "map", TimeSpec::Latest)
+            .unwrap();
+        assert!(out.markers.iter().all(|m| m.header.frame_id == "map"));
+        assert_position(&out.markers[0].pose, [1.0, 1.0]);
+        assert_position(&out.markers[1].pose, [0.0, 1.0]);
+    }
+}
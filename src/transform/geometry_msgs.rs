@@ -1,23 +1,758 @@
+//! The stamped `geometry_msgs` types are transformed in Rust, following
+//! tf2_geometry_msgs' `doTransform`. The C++ wrappers around it are only kept
+//! as a reference for the tests.
+
+use crate::Tf2Error;
+use crate::math;
+use crate::transform::{Transformable, TransformablePayload, restamp};
 use crate::transform_stamped::TransformStamped;
 
-impl From<&geometry_msgs::msg::TransformStamped> for TransformStamped {
-    fn from(t: &geometry_msgs::msg::TransformStamped) -> Self {
+impl Transformable for geometry_msgs::msg::PointStamped {
+    fn apply_transform(&self, tf: &TransformStamped) -> Result<Self, Tf2Error> {
+        let mut out = self.clone();
+        restamp(&mut out.header, tf);
+        out.point = self.point.transform_payload(tf)?;
+        Ok(out)
+    }
+}
+
+impl Transformable for geometry_msgs::msg::PoseStamped {
+    fn apply_transform(&self, tf: &TransformStamped) -> Result<Self, Tf2Error> {
+        let mut out = self.clone();
+        restamp(&mut out.header, tf);
+        out.pose = self.pose.transform_payload(tf)?;
+        Ok(out)
+    }
+}
+
+/// Rotation only: the translation of `tf` is ignored.
+impl Transformable for geometry_msgs::msg::Vector3Stamped {
+    fn apply_transform(&self, tf: &TransformStamped) -> Result<Self, Tf2Error> {
+        let mut out = self.clone();
+        restamp(&mut out.header, tf);
+        out.vector = self.vector.transform_payload(tf)?;
+        Ok(out)
+    }
+}
+
+impl Transformable for geometry_msgs::msg::QuaternionStamped {
+    fn apply_transform(&self, tf: &TransformStamped) -> Result<Self, Tf2Error> {
+        let mut out = self.clone();
+        restamp(&mut out.header, tf);
+        out.quaternion = self.quaternion.transform_payload(tf)?;
+        Ok(out)
+    }
+}
+
+/// Composition: `tf` (target <- frame_id) applied to `frame_id <- child_frame_id`
+/// yields `target <- child_frame_id`, stamped like `tf`. Fails if `tf` does
+/// not end in `frame_id`.
+impl Transformable for geometry_msgs::msg::TransformStamped {
+    fn apply_transform(&self, tf: &TransformStamped) -> Result<Self, Tf2Error> {
+        let mut out: Self = tf.compose(&TransformStamped::from(self))?.into();
+        restamp(&mut out.header, tf);
+        Ok(out)
+    }
+}
+
+/// The pose is transformed and the covariance rotated into the target frame.
+impl Transformable for geometry_msgs::msg::PoseWithCovarianceStamped {
+    fn apply_transform(&self, tf: &TransformStamped) -> Result<Self, Tf2Error> {
+        let mut out = self.clone();
+        restamp(&mut out.header, tf);
+        out.pose.pose = self.pose.pose.transform_payload(tf)?;
+        out.pose.covariance = rotate_covariance(&self.pose.covariance, tf.rotation);
+        Ok(out)
+    }
+}
+
+/// Linear and angular parts are rotated only, like tf2 does for wrenches.
+impl Transformable for geometry_msgs::msg::TwistStamped {
+    fn apply_transform(&self, tf: &TransformStamped) -> Result<Self, Tf2Error> {
+        let mut out = self.clone();
+        restamp(&mut out.header, tf);
+        out.twist.linear = self.twist.linear.transform_payload(tf)?;
+        out.twist.angular = self.twist.angular.transform_payload(tf)?;
+        Ok(out)
+    }
+}
+
+impl Transformable for geometry_msgs::msg::WrenchStamped {
+    fn apply_transform(&self, tf: &TransformStamped) -> Result<Self, Tf2Error> {
+        let mut out = self.clone();
+        restamp(&mut out.header, tf);
+        out.wrench.force = self.wrench.force.transform_payload(tf)?;
+        out.wrench.torque = self.wrench.torque.transform_payload(tf)?;
+        Ok(out)
+    }
+}
+
+impl Transformable for geometry_msgs::msg::PolygonStamped {
+    fn apply_transform(&self, tf: &TransformStamped) -> Result<Self, Tf2Error> {
+        let mut out = self.clone();
+        restamp(&mut out.header, tf);
+        out.polygon.points = self.polygon.points.transform_payload(tf)?;
+        Ok(out)
+    }
+}
+
+/// `R * C * R^T` for each 3x3 block of the row-major 6x6 `covariance`, as
+/// tf2's `transformCovariance` does.
+fn rotate_covariance(covariance: &[f64; 36], rotation: math::Quat) -> [f64; 36] {
+    let r = math::quat_to_matrix(rotation);
+    let mut out = [0.0; 36];
+    for row in 0..6 {
+        for col in 0..6 {
+            let (block_row, block_col) = (row / 3 * 3, col / 3 * 3);
+            let (i, j) = (row % 3, col % 3);
+            let mut sum = 0.0;
+            for k in 0..3 {
+                for l in 0..3 {
+                    sum += r[i][k] * covariance[(block_row + k) * 6 + block_col + l] * r[j][l];
+                }
+            }
+            out[row * 6 + col] = sum;
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn make_tf(
+        parent_frame: &str, // target frame (tf header.frame_id)
+        child_frame: &str,  // source frame (tf child_frame_id)
+        translation: [f64; 3],
+        rotation_xyzw: [f64; 4],
+        stamp: (i32, u32),
+    ) -> TransformStamped {
         TransformStamped {
-            stamp_sec: t.header.stamp.sec,
-            stamp_nanosec: t.header.stamp.nanosec,
-            parent_frame: t.header.frame_id.clone(),
-            child_frame: t.child_frame_id.clone(),
-            translation: [
-                t.transform.translation.x,
-                t.transform.translation.y,
-                t.transform.translation.z,
-            ],
-            rotation: [
-                t.transform.rotation.x,
-                t.transform.rotation.y,
-                t.transform.rotation.z,
-                t.transform.rotation.w,
-            ],
+            stamp_sec: stamp.0,
+            stamp_nanosec: stamp.1,
+            parent_frame: parent_frame.to_string(),
+            child_frame: child_frame.to_string(),
+            translation,
+            rotation: rotation_xyzw,
+        }
+    }
+
+    fn make_point(frame: &str, p: [f64; 3]) -> geometry_msgs::msg::PointStamped {
+        let mut msg = geometry_msgs::msg::PointStamped::default();
+        msg.header.frame_id = frame.to_string();
+        msg.point.x = p[0];
+        msg.point.y = p[1];
+        msg.point.z = p[2];
+        msg
+    }
+
+    fn make_pose(frame: &str, p: [f64; 3], q: [f64; 4]) -> geometry_msgs::msg::PoseStamped {
+        let mut msg = geometry_msgs::msg::PoseStamped::default();
+        msg.header.frame_id = frame.to_string();
+        msg.pose.position.x = p[0];
+        msg.pose.position.y = p[1];
+        msg.pose.position.z = p[2];
+        msg.pose.orientation.x = q[0];
+        msg.pose.orientation.y = q[1];
+        msg.pose.orientation.z = q[2];
+        msg.pose.orientation.w = q[3];
+        msg
+    }
+
+    fn assert_near(a: f64, b: f64, eps: f64, label: &str) {
+        if (a - b).abs() > eps {
+            panic!("{label}: {a} vs {b} (eps={eps})");
+        }
+    }
+
+    #[test]
+    fn transform_point_stamped_translation_only() {
+        let tf = make_tf(
+            "map",
+            "base_link",
+            [10.0, -2.0, 0.5],
+            [0.0, 0.0, 0.0, 1.0],
+            (123, 456),
+        );
+
+        let out = make_point("base_link", [1.0, 2.0, 3.0])
+            .apply_transform(&tf)
+            .expect("transform should succeed");
+
+        assert_eq!(out.header.frame_id, "map");
+        assert_eq!(out.header.stamp.sec, 123);
+        assert_eq!(out.header.stamp.nanosec, 456);
+
+        assert_near(out.point.x, 11.0, 1e-9, "x");
+        assert_near(out.point.y, 0.0, 1e-9, "y");
+        assert_near(out.point.z, 3.5, 1e-9, "z");
+    }
+
+    #[test]
+    fn transform_point_stamped_rotate_z_90deg() {
+        let s = (0.5f64).sqrt();
+        let tf = make_tf("map", "base_link", [0.0, 0.0, 0.0], [0.0, 0.0, s, s], (0, 0));
+
+        let out = make_point("base_link", [1.0, 0.0, 0.0])
+            .apply_transform(&tf)
+            .expect("transform should succeed");
+
+        // (1,0,0) rotated +90deg about Z -> (0,1,0)
+        assert_near(out.point.x, 0.0, 1e-9, "x");
+        assert_near(out.point.y, 1.0, 1e-9, "y");
+        assert_near(out.point.z, 0.0, 1e-9, "z");
+    }
+
+    #[test]
+    fn transform_pose_stamped_translation_and_rotation() {
+        // 90 deg yaw about +Z, then shift by (1,0,0).
+        let s = (0.5f64).sqrt();
+        let tf = make_tf("map", "base_link", [1.0, 0.0, 0.0], [0.0, 0.0, s, s], (7, 8));
+
+        let out = make_pose("base_link", [1.0, 0.0, 0.0], [0.0, 0.0, s, s])
+            .apply_transform(&tf)
+            .expect("transform should succeed");
+
+        assert_eq!(out.header.frame_id, "map");
+        assert_eq!(out.header.stamp.sec, 7);
+        assert_eq!(out.header.stamp.nanosec, 8);
+
+        assert_near(out.pose.position.x, 1.0, 1e-9, "x");
+        assert_near(out.pose.position.y, 1.0, 1e-9, "y");
+        assert_near(out.pose.position.z, 0.0, 1e-9, "z");
+
+        // Two 90 deg yaws compose into 180 deg: q = [0,0,1,0] (up to sign).
+        let q = &out.pose.orientation;
+        assert_near(q.x, 0.0, 1e-9, "qx");
+        assert_near(q.y, 0.0, 1e-9, "qy");
+        assert_near(q.z.abs(), 1.0, 1e-9, "qz");
+        assert_near(q.w, 0.0, 1e-9, "qw");
+    }
+
+    #[test]
+    fn transform_vector3_stamped_ignores_translation() {
+        let s = (0.5f64).sqrt();
+        let tf = make_tf("map", "base_link", [5.0, 5.0, 5.0], [0.0, 0.0, s, s], (0, 0));
+
+        let mut msg = geometry_msgs::msg::Vector3Stamped::default();
+        msg.header.frame_id = "base_link".to_string();
+        msg.vector.x = 1.0;
+
+        let out = msg.apply_transform(&tf).expect("transform should succeed");
+
+        assert_eq!(out.header.frame_id, "map");
+        assert_near(out.vector.x, 0.0, 1e-9, "x");
+        assert_near(out.vector.y, 1.0, 1e-9, "y");
+        assert_near(out.vector.z, 0.0, 1e-9, "z");
+    }
+
+    #[test]
+    fn transform_transform_stamped_composes() {
+        // map <- odom, applied to odom <- base_link, gives map <- base_link.
+        let map_odom = make_tf("map", "odom", [1.0, 0.0, 0.0], [0.0, 0.0, 0.0, 1.0], (3, 0));
+        let odom_base: geometry_msgs::msg::TransformStamped =
+            make_tf("odom", "base_link", [0.0, 2.0, 0.0], [0.0, 0.0, 0.0, 1.0], (3, 0)).into();
+
+        let out = odom_base
+            .apply_transform(&map_odom)
+            .expect("transform should succeed");
+
+        assert_eq!(out.header.frame_id, "map");
+        assert_eq!(out.child_frame_id, "base_link");
+        assert_near(out.transform.translation.x, 1.0, 1e-9, "x");
+        assert_near(out.transform.translation.y, 2.0, 1e-9, "y");
+        assert_near(out.transform.translation.z, 0.0, 1e-9, "z");
+    }
+
+    #[test]
+    fn transform_pose_with_covariance_rotates_covariance() {
+        let s = (0.5f64).sqrt();
+        let tf = make_tf("map", "base_link", [0.0, 0.0, 0.0], [0.0, 0.0, s, s], (0, 0));
+
+        let mut msg = geometry_msgs::msg::PoseWithCovarianceStamped::default();
+        msg.header.frame_id = "base_link".to_string();
+        msg.pose.pose.orientation.w = 1.0;
+        // Variance only along x.
+        msg.pose.covariance[0] = 4.0;
+
+        let out = msg.apply_transform(&tf).expect("transform should succeed");
+
+        // After a 90 deg yaw the variance lies along y.
+        assert_near(out.pose.covariance[0], 0.0, 1e-9, "cov_xx");
+        assert_near(out.pose.covariance[7], 4.0, 1e-9, "cov_yy");
+    }
+
+    #[test]
+    fn transform_polygon_stamped_moves_every_point() {
+        let tf = make_tf("map", "base_link", [1.0, 0.0, 0.0], [0.0, 0.0, 0.0, 1.0], (0, 0));
+
+        let mut msg = geometry_msgs::msg::PolygonStamped::default();
+        msg.header.frame_id = "base_link".to_string();
+        for x in [0.0f32, 1.0, 2.0] {
+            msg.polygon.points.push(geometry_msgs::msg::Point32 {
+                x,
+                ..Default::default()
+            });
+        }
+
+        let out = msg.apply_transform(&tf).expect("transform should succeed");
+
+        assert_eq!(out.header.frame_id, "map");
+        let xs: Vec<f32> = out.polygon.points.iter().map(|p| p.x).collect();
+        assert_eq!(xs, vec![1.0, 2.0, 3.0]);
+    }
+
+    #[cfg(feature = "tf2-cpp")]
+    mod tf2_oracle {
+        use super::*;
+        use crate::ffi::ffi;
+        use crate::ffi_utils::call_out;
+
+        /// Transforms `msg` with tf2's own `doTransform`, through the ffi
+        /// mirror of its type.
+        fn transform_with_tf2<M, F>(
+            msg: &M,
+            tf: &TransformStamped,
+            do_transform: fn(&F, &ffi::Tf2TransformStamped, &mut F) -> ffi::Tf2Status,
+        ) -> M
+        where
+            for<'a> F: Default + From<&'a M>,
+            M: From<F>,
+        {
+            let ffi_in = F::from(msg);
+            call_out(|out| do_transform(&ffi_in, &tf.to_ffi(), out))
+                .expect("tf2 should transform the message")
+                .into()
+        }
+
+        fn header_to_ffi(h: &std_msgs::msg::Header) -> ffi::Tf2Header {
+            ffi::Tf2Header {
+                stamp: ffi::Tf2Time {
+                    sec: h.stamp.sec,
+                    nanosec: h.stamp.nanosec,
+                },
+                frame_id: h.frame_id.clone(),
+            }
+        }
+
+        fn header_from_ffi(h: ffi::Tf2Header) -> std_msgs::msg::Header {
+            let mut out = std_msgs::msg::Header::default();
+            out.stamp.sec = h.stamp.sec;
+            out.stamp.nanosec = h.stamp.nanosec;
+            out.frame_id = h.frame_id;
+            out
+        }
+
+        fn assert_all_near(ours: &[f64], theirs: &[f64], label: &str) {
+            assert_eq!(ours.len(), theirs.len(), "{label}: length");
+            for (a, b) in ours.iter().zip(theirs) {
+                assert_near(*a, *b, 1e-9, label);
+            }
+        }
+
+        #[test]
+        fn matches_tf2_geometry_msgs() {
+            let tf = TransformStamped {
+                stamp_sec: 12,
+                stamp_nanosec: 345,
+                ..TransformStamped::from_euler(
+                    "map",
+                    "base_link",
+                    [0.3, -2.0, 1.5],
+                    [0.1, -0.4, 2.0],
+                )
+            };
+
+            let pose = make_pose(
+                "base_link",
+                [1.0, -2.0, 0.5],
+                [0.1, 0.2, -0.3, 0.927_361_849_549_570_3],
+            );
+            let ours = pose.apply_transform(&tf).unwrap();
+            let theirs = transform_with_tf2(&pose, &tf, ffi::do_transform_pose_stamped);
+            assert_eq!(ours.header.frame_id, theirs.header.frame_id);
+            assert_eq!(
+                (ours.header.stamp.sec, ours.header.stamp.nanosec),
+                (theirs.header.stamp.sec, theirs.header.stamp.nanosec)
+            );
+            let flat = |p: &geometry_msgs::msg::Pose| {
+                [
+                    p.position.x,
+                    p.position.y,
+                    p.position.z,
+                    p.orientation.x,
+                    p.orientation.y,
+                    p.orientation.z,
+                    p.orientation.w,
+                ]
+            };
+            assert_all_near(&flat(&ours.pose), &flat(&theirs.pose), "pose");
+
+            let point = make_point("base_link", [4.0, 5.0, -6.0]);
+            let ours = point.apply_transform(&tf).unwrap().point;
+            let theirs = transform_with_tf2(&point, &tf, ffi::do_transform_point_stamped).point;
+            assert_all_near(
+                &[ours.x, ours.y, ours.z],
+                &[theirs.x, theirs.y, theirs.z],
+                "point",
+            );
+
+            let mut vector = geometry_msgs::msg::Vector3Stamped::default();
+            vector.header.frame_id = "base_link".to_string();
+            (vector.vector.x, vector.vector.y, vector.vector.z) = (1.0, -1.0, 2.0);
+            let ours = vector.apply_transform(&tf).unwrap().vector;
+            let theirs = transform_with_tf2(&vector, &tf, ffi::do_transform_vector3_stamped).vector;
+            assert_all_near(
+                &[ours.x, ours.y, ours.z],
+                &[theirs.x, theirs.y, theirs.z],
+                "vector",
+            );
+
+            let mut quaternion = geometry_msgs::msg::QuaternionStamped::default();
+            quaternion.header.frame_id = "base_link".to_string();
+            quaternion.quaternion = pose.pose.orientation.clone();
+            let ours = quaternion.apply_transform(&tf).unwrap().quaternion;
+            let theirs = transform_with_tf2(&quaternion, &tf, ffi::do_transform_quaternion_stamped)
+                .quaternion;
+            assert_all_near(
+                &[ours.x, ours.y, ours.z, ours.w],
+                &[theirs.x, theirs.y, theirs.z, theirs.w],
+                "quaternion",
+            );
+
+            let transform: geometry_msgs::msg::TransformStamped = TransformStamped::from_euler(
+                "base_link",
+                "camera",
+                [0.2, 0.0, 0.4],
+                [0.0, 0.5, -1.0],
+            )
+            .into();
+            let ours = TransformStamped::from(&transform.apply_transform(&tf).unwrap());
+            let theirs = TransformStamped::from(&transform_with_tf2(
+                &transform,
+                &tf,
+                ffi::do_transform_transform_stamped,
+            ));
+            assert_eq!(ours.child_frame, theirs.child_frame);
+            assert_eq!(
+                (ours.stamp_sec, ours.stamp_nanosec),
+                (theirs.stamp_sec, theirs.stamp_nanosec)
+            );
+            assert_all_near(
+                &ours.translation,
+                &theirs.translation,
+                "transform translation",
+            );
+            assert_all_near(&ours.rotation, &theirs.rotation, "transform rotation");
+
+            let mut with_covariance = geometry_msgs::msg::PoseWithCovarianceStamped::default();
+            with_covariance.header.frame_id = "base_link".to_string();
+            with_covariance.pose.pose = pose.pose.clone();
+            for (i, c) in with_covariance.pose.covariance.iter_mut().enumerate() {
+                // Symmetric, with distinct entries in every block.
+                let (row, col) = (i / 6, i % 6);
+                *c = if row == col {
+                    2.0 + row as f64
+                } else {
+                    0.1 * (row + col) as f64
+                };
+            }
+            let ours = with_covariance.apply_transform(&tf).unwrap().pose;
+            let theirs = transform_with_tf2(
+                &with_covariance,
+                &tf,
+                ffi::do_transform_pose_with_covariance_stamped,
+            )
+            .pose;
+            assert_all_near(
+                &flat(&ours.pose),
+                &flat(&theirs.pose),
+                "pose with covariance",
+            );
+            assert_all_near(&ours.covariance, &theirs.covariance, "covariance");
+
+            let mut twist = geometry_msgs::msg::TwistStamped::default();
+            twist.header.frame_id = "base_link".to_string();
+            (twist.twist.linear.x, twist.twist.linear.y) = (1.0, 0.5);
+            twist.twist.angular.z = -0.7;
+            let ours = twist.apply_transform(&tf).unwrap().twist;
+            let theirs = transform_with_tf2(&twist, &tf, ffi::do_transform_twist_stamped).twist;
+            for (a, b, label) in [
+                (&ours.linear, &theirs.linear, "twist linear"),
+                (&ours.angular, &theirs.angular, "twist angular"),
+            ] {
+                assert_all_near(&[a.x, a.y, a.z], &[b.x, b.y, b.z], label);
+            }
+
+            let mut wrench = geometry_msgs::msg::WrenchStamped::default();
+            wrench.header.frame_id = "base_link".to_string();
+            (wrench.wrench.force.x, wrench.wrench.force.z) = (3.0, -9.8);
+            wrench.wrench.torque.y = 0.25;
+            let ours = wrench.apply_transform(&tf).unwrap().wrench;
+            let theirs = transform_with_tf2(&wrench, &tf, ffi::do_transform_wrench_stamped).wrench;
+            for (a, b, label) in [
+                (&ours.force, &theirs.force, "force"),
+                (&ours.torque, &theirs.torque, "torque"),
+            ] {
+                assert_all_near(&[a.x, a.y, a.z], &[b.x, b.y, b.z], label);
+            }
+
+            let mut polygon = geometry_msgs::msg::PolygonStamped::default();
+            polygon.header.frame_id = "base_link".to_string();
+            for i in 0..4 {
+                polygon.polygon.points.push(geometry_msgs::msg::Point32 {
+                    x: i as f32,
+                    y: 1.0 - i as f32,
+                    z: 0.5,
+                });
+            }
+            let ours = polygon.apply_transform(&tf).unwrap().polygon.points;
+            let theirs = transform_with_tf2(&polygon, &tf, ffi::do_transform_polygon_stamped)
+                .polygon
+                .points;
+            assert_eq!(ours.len(), theirs.len());
+            for (a, b) in ours.iter().zip(&theirs) {
+                for (x, y) in [(a.x, b.x), (a.y, b.y), (a.z, b.z)] {
+                    assert_near(x as f64, y as f64, 1e-5, "polygon");
+                }
+            }
+        }
+
+        impl From<&geometry_msgs::msg::PointStamped> for ffi::Tf2PointStamped {
+            fn from(p: &geometry_msgs::msg::PointStamped) -> Self {
+                ffi::Tf2PointStamped {
+                    header: header_to_ffi(&p.header),
+                    x: p.point.x,
+                    y: p.point.y,
+                    z: p.point.z,
+                }
+            }
+        }
+
+        impl From<ffi::Tf2PointStamped> for geometry_msgs::msg::PointStamped {
+            fn from(p: ffi::Tf2PointStamped) -> Self {
+                let mut out = geometry_msgs::msg::PointStamped::default();
+                out.header = header_from_ffi(p.header);
+                out.point.x = p.x;
+                out.point.y = p.y;
+                out.point.z = p.z;
+                out
+            }
+        }
+
+        impl From<&geometry_msgs::msg::PoseStamped> for ffi::Tf2PoseStamped {
+            fn from(p: &geometry_msgs::msg::PoseStamped) -> Self {
+                ffi::Tf2PoseStamped {
+                    header: header_to_ffi(&p.header),
+                    position_x: p.pose.position.x,
+                    position_y: p.pose.position.y,
+                    position_z: p.pose.position.z,
+                    orientation_x: p.pose.orientation.x,
+                    orientation_y: p.pose.orientation.y,
+                    orientation_z: p.pose.orientation.z,
+                    orientation_w: p.pose.orientation.w,
+                }
+            }
+        }
+
+        impl From<ffi::Tf2PoseStamped> for geometry_msgs::msg::PoseStamped {
+            fn from(p: ffi::Tf2PoseStamped) -> Self {
+                let mut out = geometry_msgs::msg::PoseStamped::default();
+                out.header = header_from_ffi(p.header);
+                out.pose.position.x = p.position_x;
+                out.pose.position.y = p.position_y;
+                out.pose.position.z = p.position_z;
+                out.pose.orientation.x = p.orientation_x;
+                out.pose.orientation.y = p.orientation_y;
+                out.pose.orientation.z = p.orientation_z;
+                out.pose.orientation.w = p.orientation_w;
+                out
+            }
+        }
+
+        impl From<&geometry_msgs::msg::TransformStamped> for ffi::Tf2TransformStamped {
+            fn from(t: &geometry_msgs::msg::TransformStamped) -> Self {
+                TransformStamped::from(t).to_ffi()
+            }
+        }
+
+        impl From<ffi::Tf2TransformStamped> for geometry_msgs::msg::TransformStamped {
+            fn from(t: ffi::Tf2TransformStamped) -> Self {
+                TransformStamped::from_ffi(t).into()
+            }
+        }
+
+        impl From<&geometry_msgs::msg::Vector3Stamped> for ffi::Tf2Vector3Stamped {
+            fn from(v: &geometry_msgs::msg::Vector3Stamped) -> Self {
+                ffi::Tf2Vector3Stamped {
+                    header: header_to_ffi(&v.header),
+                    x: v.vector.x,
+                    y: v.vector.y,
+                    z: v.vector.z,
+                }
+            }
+        }
+
+        impl From<ffi::Tf2Vector3Stamped> for geometry_msgs::msg::Vector3Stamped {
+            fn from(v: ffi::Tf2Vector3Stamped) -> Self {
+                let mut out = geometry_msgs::msg::Vector3Stamped::default();
+                out.header = header_from_ffi(v.header);
+                out.vector.x = v.x;
+                out.vector.y = v.y;
+                out.vector.z = v.z;
+                out
+            }
+        }
+
+        impl From<&geometry_msgs::msg::QuaternionStamped> for ffi::Tf2QuaternionStamped {
+            fn from(q: &geometry_msgs::msg::QuaternionStamped) -> Self {
+                ffi::Tf2QuaternionStamped {
+                    header: header_to_ffi(&q.header),
+                    x: q.quaternion.x,
+                    y: q.quaternion.y,
+                    z: q.quaternion.z,
+                    w: q.quaternion.w,
+                }
+            }
+        }
+
+        impl From<ffi::Tf2QuaternionStamped> for geometry_msgs::msg::QuaternionStamped {
+            fn from(q: ffi::Tf2QuaternionStamped) -> Self {
+                let mut out = geometry_msgs::msg::QuaternionStamped::default();
+                out.header = header_from_ffi(q.header);
+                out.quaternion.x = q.x;
+                out.quaternion.y = q.y;
+                out.quaternion.z = q.z;
+                out.quaternion.w = q.w;
+                out
+            }
+        }
+
+        impl From<&geometry_msgs::msg::PoseWithCovarianceStamped> for ffi::Tf2PoseWithCovarianceStamped {
+            fn from(p: &geometry_msgs::msg::PoseWithCovarianceStamped) -> Self {
+                ffi::Tf2PoseWithCovarianceStamped {
+                    header: header_to_ffi(&p.header),
+                    position_x: p.pose.pose.position.x,
+                    position_y: p.pose.pose.position.y,
+                    position_z: p.pose.pose.position.z,
+                    orientation_x: p.pose.pose.orientation.x,
+                    orientation_y: p.pose.pose.orientation.y,
+                    orientation_z: p.pose.pose.orientation.z,
+                    orientation_w: p.pose.pose.orientation.w,
+                    covariance: p.pose.covariance.to_vec(),
+                }
+            }
+        }
+
+        impl From<ffi::Tf2PoseWithCovarianceStamped> for geometry_msgs::msg::PoseWithCovarianceStamped {
+            fn from(p: ffi::Tf2PoseWithCovarianceStamped) -> Self {
+                let mut out = geometry_msgs::msg::PoseWithCovarianceStamped::default();
+                out.header = header_from_ffi(p.header);
+                out.pose.pose.position.x = p.position_x;
+                out.pose.pose.position.y = p.position_y;
+                out.pose.pose.position.z = p.position_z;
+                out.pose.pose.orientation.x = p.orientation_x;
+                out.pose.pose.orientation.y = p.orientation_y;
+                out.pose.pose.orientation.z = p.orientation_z;
+                out.pose.pose.orientation.w = p.orientation_w;
+                // The C++ side always hands back the full 6x6 matrix.
+                out.pose.covariance.copy_from_slice(&p.covariance);
+                out
+            }
+        }
+
+        impl From<&geometry_msgs::msg::TwistStamped> for ffi::Tf2TwistStamped {
+            fn from(t: &geometry_msgs::msg::TwistStamped) -> Self {
+                ffi::Tf2TwistStamped {
+                    header: header_to_ffi(&t.header),
+                    linear_x: t.twist.linear.x,
+                    linear_y: t.twist.linear.y,
+                    linear_z: t.twist.linear.z,
+                    angular_x: t.twist.angular.x,
+                    angular_y: t.twist.angular.y,
+                    angular_z: t.twist.angular.z,
+                }
+            }
+        }
+
+        impl From<ffi::Tf2TwistStamped> for geometry_msgs::msg::TwistStamped {
+            fn from(t: ffi::Tf2TwistStamped) -> Self {
+                let mut out = geometry_msgs::msg::TwistStamped::default();
+                out.header = header_from_ffi(t.header);
+                out.twist.linear.x = t.linear_x;
+                out.twist.linear.y = t.linear_y;
+                out.twist.linear.z = t.linear_z;
+                out.twist.angular.x = t.angular_x;
+                out.twist.angular.y = t.angular_y;
+                out.twist.angular.z = t.angular_z;
+                out
+            }
+        }
+
+        impl From<&geometry_msgs::msg::WrenchStamped> for ffi::Tf2WrenchStamped {
+            fn from(w: &geometry_msgs::msg::WrenchStamped) -> Self {
+                ffi::Tf2WrenchStamped {
+                    header: header_to_ffi(&w.header),
+                    force_x: w.wrench.force.x,
+                    force_y: w.wrench.force.y,
+                    force_z: w.wrench.force.z,
+                    torque_x: w.wrench.torque.x,
+                    torque_y: w.wrench.torque.y,
+                    torque_z: w.wrench.torque.z,
+                }
+            }
+        }
+
+        impl From<ffi::Tf2WrenchStamped> for geometry_msgs::msg::WrenchStamped {
+            fn from(w: ffi::Tf2WrenchStamped) -> Self {
+                let mut out = geometry_msgs::msg::WrenchStamped::default();
+                out.header = header_from_ffi(w.header);
+                out.wrench.force.x = w.force_x;
+                out.wrench.force.y = w.force_y;
+                out.wrench.force.z = w.force_z;
+                out.wrench.torque.x = w.torque_x;
+                out.wrench.torque.y = w.torque_y;
+                out.wrench.torque.z = w.torque_z;
+                out
+            }
+        }
+
+        impl From<&geometry_msgs::msg::PolygonStamped> for ffi::Tf2PolygonStamped {
+            fn from(p: &geometry_msgs::msg::PolygonStamped) -> Self {
+                ffi::Tf2PolygonStamped {
+                    header: header_to_ffi(&p.header),
+                    points: p
+                        .polygon
+                        .points
+                        .iter()
+                        .map(|pt| ffi::Tf2Point32 {
+                            x: pt.x,
+                            y: pt.y,
+                            z: pt.z,
+                        })
+                        .collect(),
+                }
+            }
+        }
+
+        impl From<ffi::Tf2PolygonStamped> for geometry_msgs::msg::PolygonStamped {
+            fn from(p: ffi::Tf2PolygonStamped) -> Self {
+                let mut out = geometry_msgs::msg::PolygonStamped::default();
+                out.header = header_from_ffi(p.header);
+                out.polygon.points = p
+                    .points
+                    .into_iter()
+                    .map(|pt| {
+                        let mut point = geometry_msgs::msg::Point32::default();
+                        point.x = pt.x;
+                        point.y = pt.y;
+                        point.z = pt.z;
+                        point
+                    })
+                    .collect();
+                out
+            }
         }
     }
 }
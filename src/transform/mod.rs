@@ -1,7 +1,8 @@
+pub mod collections;
 pub mod geometry_msgs;
 pub mod sensor_msgs;
 
-use crate::ffi::ffi;
+use crate::math;
 use crate::{Tf2Error, TransformStamped};
 
 pub trait HasHeader {
@@ -11,24 +12,82 @@ pub trait HasHeader {
 
 pub trait Transformable: HasHeader + Sized {
     fn apply_transform(&self, tf: &TransformStamped) -> Result<Self, Tf2Error>;
+
+    /// Same as [`apply_transform`](Transformable::apply_transform), writing
+    /// into `self`. Large messages override it to skip the copy.
+    fn apply_transform_in_place(&mut self, tf: &TransformStamped) -> Result<(), Tf2Error> {
+        *self = self.apply_transform(tf)?;
+        Ok(())
+    }
+}
+
+/// Data without a header of its own that can be moved between frames.
+/// Wrapping it in a [`Stamped`](crate::Stamped) makes it [`Transformable`].
+pub trait TransformablePayload: Sized {
+    /// Expresses `self`, given in `tf.child_frame`, in `tf.parent_frame`.
+    fn transform_payload(&self, tf: &TransformStamped) -> Result<Self, Tf2Error>;
+}
+
+impl<T: TransformablePayload> TransformablePayload for Vec<T> {
+    fn transform_payload(&self, tf: &TransformStamped) -> Result<Self, Tf2Error> {
+        self.iter().map(|v| v.transform_payload(tf)).collect()
+    }
+}
+
+impl<T: TransformablePayload> TransformablePayload for Option<T> {
+    fn transform_payload(&self, tf: &TransformStamped) -> Result<Self, Tf2Error> {
+        self.as_ref().map(|v| v.transform_payload(tf)).transpose()
+    }
 }
 
-pub(crate) fn header_to_ffi(h: &std_msgs::msg::Header) -> ffi::Tf2Header {
-    ffi::Tf2Header {
-        stamp: ffi::Tf2Time {
-            sec: h.stamp.sec,
-            nanosec: h.stamp.nanosec,
-        },
-        frame_id: h.frame_id.clone(),
+impl TransformablePayload for ::geometry_msgs::msg::Point {
+    fn transform_payload(&self, tf: &TransformStamped) -> Result<Self, Tf2Error> {
+        let [x, y, z] = tf.transform_point([self.x, self.y, self.z]);
+        Ok(Self { x, y, z })
     }
 }
 
-pub(crate) fn header_from_ffi(h: ffi::Tf2Header) -> std_msgs::msg::Header {
-    let mut out = std_msgs::msg::Header::default();
-    out.stamp.sec = h.stamp.sec;
-    out.stamp.nanosec = h.stamp.nanosec;
-    out.frame_id = h.frame_id;
-    out
+/// Computed in `f64` and rounded back, like tf2's `PolygonStamped`.
+impl TransformablePayload for ::geometry_msgs::msg::Point32 {
+    fn transform_payload(&self, tf: &TransformStamped) -> Result<Self, Tf2Error> {
+        let [x, y, z] = tf.transform_point([self.x as f64, self.y as f64, self.z as f64]);
+        Ok(Self {
+            x: x as f32,
+            y: y as f32,
+            z: z as f32,
+        })
+    }
+}
+
+/// Rotated only, like tf2's `Vector3Stamped`.
+impl TransformablePayload for ::geometry_msgs::msg::Vector3 {
+    fn transform_payload(&self, tf: &TransformStamped) -> Result<Self, Tf2Error> {
+        let [x, y, z] = tf.transform_vector([self.x, self.y, self.z]);
+        Ok(Self { x, y, z })
+    }
+}
+
+impl TransformablePayload for ::geometry_msgs::msg::Quaternion {
+    fn transform_payload(&self, tf: &TransformStamped) -> Result<Self, Tf2Error> {
+        let [x, y, z, w] = math::quat_mul(tf.rotation, [self.x, self.y, self.z, self.w]);
+        Ok(Self { x, y, z, w })
+    }
+}
+
+impl TransformablePayload for ::geometry_msgs::msg::Pose {
+    fn transform_payload(&self, tf: &TransformStamped) -> Result<Self, Tf2Error> {
+        Ok(Self {
+            position: self.position.transform_payload(tf)?,
+            orientation: self.orientation.transform_payload(tf)?,
+        })
+    }
+}
+
+/// Gives `header` the frame and stamp of `tf`, as tf2's `doTransform` does.
+pub(crate) fn restamp(header: &mut std_msgs::msg::Header, tf: &TransformStamped) {
+    header.frame_id = tf.parent_frame.clone();
+    header.stamp.sec = tf.stamp_sec;
+    header.stamp.nanosec = tf.stamp_nanosec;
 }
 
 #[macro_export]
@@ -44,3 +103,17 @@ macro_rules! impl_has_header_for_ros2_msg {
         }
     };
 }
+
+impl_has_header_for_ros2_msg!(::geometry_msgs::msg::PointStamped);
+impl_has_header_for_ros2_msg!(::geometry_msgs::msg::PoseStamped);
+impl_has_header_for_ros2_msg!(::geometry_msgs::msg::Vector3Stamped);
+impl_has_header_for_ros2_msg!(::geometry_msgs::msg::QuaternionStamped);
+impl_has_header_for_ros2_msg!(::geometry_msgs::msg::TransformStamped);
+impl_has_header_for_ros2_msg!(::geometry_msgs::msg::PoseWithCovarianceStamped);
+impl_has_header_for_ros2_msg!(::geometry_msgs::msg::TwistStamped);
+impl_has_header_for_ros2_msg!(::geometry_msgs::msg::WrenchStamped);
+impl_has_header_for_ros2_msg!(::geometry_msgs::msg::PolygonStamped);
+impl_has_header_for_ros2_msg!(::geometry_msgs::msg::PoseArray);
+impl_has_header_for_ros2_msg!(::nav_msgs::msg::Path);
+impl_has_header_for_ros2_msg!(::visualization_msgs::msg::Marker);
+impl_has_header_for_ros2_msg!(::sensor_msgs::msg::PointCloud2);
@@ -1,88 +1,317 @@
+//! `PointCloud2` is transformed in Rust, directly on its `data` buffer. The
+//! tf2_sensor_msgs path through C++ copies the cloud four times and is only
+//! kept as a reference for the tests.
+
 use crate::Tf2Error;
 use crate::TransformStamped;
-use crate::ffi::ffi;
-use crate::ffi_utils::call_out;
+use crate::math;
 use crate::transform::Transformable;
 
-crate::impl_has_header_for_ros2_msg!(sensor_msgs::msg::PointCloud2);
+// ROS PointField datatype enum.
+const PF_INT8: u8 = 1;
+const PF_UINT8: u8 = 2;
+const PF_INT16: u8 = 3;
+const PF_UINT16: u8 = 4;
+const PF_INT32: u8 = 5;
+const PF_UINT32: u8 = 6;
+const PF_FLOAT32: u8 = 7;
+const PF_FLOAT64: u8 = 8;
 
 impl Transformable for sensor_msgs::msg::PointCloud2 {
     fn apply_transform(&self, tf: &TransformStamped) -> Result<Self, Tf2Error> {
-        let ffi_in: ffi::Tf2PointCloud2 = self.into();
-        let ffi_out = call_out(|out| ffi::do_transform_pointcloud2(&ffi_in, &tf.to_ffi(), out))?;
-        Ok((ffi_out).into())
+        let mut out = self.clone();
+        out.apply_transform_in_place(tf)?;
+        Ok(out)
+    }
+
+    /// Moves `x`/`y`/`z` and the `vp_x`/`vp_y`/`vp_z` viewpoint, and rotates
+    /// `normal_x`/`normal_y`/`normal_z`, for whichever are present. They may
+    /// use any `PointField` datatype and either byte order; integer fields
+    /// are rounded and saturate at their bounds. Every other field and byte
+    /// is left untouched.
+    fn apply_transform_in_place(&mut self, tf: &TransformStamped) -> Result<(), Tf2Error> {
+        let layout = CloudLayout::of(self)?;
+        let rotation = math::quat_to_matrix(tf.rotation);
+
+        layout.transform(&mut self.data, layout.xyz, rotation, tf.translation);
+        if let Some(viewpoint) = layout.viewpoint {
+            layout.transform(&mut self.data, viewpoint, rotation, tf.translation);
+        }
+        if let Some(normal) = layout.normal {
+            layout.transform(&mut self.data, normal, rotation, [0.0; 3]);
+        }
+
+        self.header.frame_id = tf.parent_frame.clone();
+        self.header.stamp.sec = tf.stamp_sec;
+        self.header.stamp.nanosec = tf.stamp_nanosec;
+        Ok(())
     }
 }
 
-impl From<&sensor_msgs::msg::PointCloud2> for ffi::Tf2PointCloud2 {
-    fn from(pc: &sensor_msgs::msg::PointCloud2) -> Self {
-        ffi::Tf2PointCloud2 {
-            header: ffi::Tf2Header {
-                stamp: ffi::Tf2Time {
-                    sec: pc.header.stamp.sec,
-                    nanosec: pc.header.stamp.nanosec,
-                },
-                frame_id: pc.header.frame_id.clone(),
-            },
-            height: pc.height,
-            width: pc.width,
-            fields: pc
-                .fields
-                .iter()
-                .map(|f| ffi::Tf2PointField {
-                    name: f.name.clone(),
-                    offset: f.offset,
-                    datatype: f.datatype,
-                    count: f.count,
-                })
-                .collect(),
-            is_bigendian: pc.is_bigendian,
-            point_step: pc.point_step,
-            row_step: pc.row_step,
-            data: pc.data.clone(),
-            is_dense: pc.is_dense,
+/// Three fields read as one vector. They share a datatype.
+#[derive(Clone, Copy)]
+struct VectorFields {
+    offsets: [usize; 3],
+    datatype: u8,
+}
+
+/// Where the transformed fields sit in a cloud whose size has been checked
+/// against its `data`.
+struct CloudLayout {
+    xyz: VectorFields,
+    viewpoint: Option<VectorFields>,
+    normal: Option<VectorFields>,
+    big_endian: bool,
+    width: usize,
+    height: usize,
+    point_step: usize,
+    row_step: usize,
+}
+
+impl CloudLayout {
+    fn of(cloud: &sensor_msgs::msg::PointCloud2) -> Result<Self, Tf2Error> {
+        let layout = Self {
+            xyz: vector_fields(cloud, ["x", "y", "z"])?
+                .ok_or_else(|| invalid("PointCloud2 has no x/y/z fields".to_string()))?,
+            viewpoint: vector_fields(cloud, ["vp_x", "vp_y", "vp_z"])?,
+            normal: vector_fields(cloud, ["normal_x", "normal_y", "normal_z"])?,
+            big_endian: cloud.is_bigendian,
+            width: cloud.width as usize,
+            height: cloud.height as usize,
+            point_step: cloud.point_step as usize,
+            row_step: cloud.row_step as usize,
+        };
+
+        let too_large = || {
+            invalid(format!(
+                "{} x {} points of {} bytes with row_step {} overflow the address space",
+                layout.height, layout.width, layout.point_step, layout.row_step
+            ))
+        };
+        let row_len = layout
+            .width
+            .checked_mul(layout.point_step)
+            .ok_or_else(too_large)?;
+        if layout.height > 1 && layout.row_step < row_len {
+            return Err(invalid(format!(
+                "row_step {} is shorter than width {} * point_step {}",
+                layout.row_step, layout.width, layout.point_step
+            )));
+        }
+        let needed = match layout.height {
+            0 => 0,
+            h => (h - 1)
+                .checked_mul(layout.row_step)
+                .and_then(|rows| rows.checked_add(row_len))
+                .ok_or_else(too_large)?,
+        };
+        if cloud.data.len() < needed {
+            return Err(invalid(format!(
+                "data holds {} bytes, layout needs {needed}",
+                cloud.data.len()
+            )));
+        }
+        Ok(layout)
+    }
+
+    /// Applies `rotation` then `translation` to `fields` in every point,
+    /// picking the element codec once for the whole cloud.
+    fn transform(
+        &self,
+        data: &mut [u8],
+        fields: VectorFields,
+        rotation: [[f64; 3]; 3],
+        translation: [f64; 3],
+    ) {
+        let [x, y, z] = fields.offsets;
+        if !self.big_endian && fields.datatype == PF_FLOAT32 && y == x + 4 && z == x + 8 {
+            self.transform_packed_f32(data, x, rotation, translation)
+        } else if self.big_endian {
+            self.transform_with_order::<true>(data, fields, rotation, translation)
+        } else {
+            self.transform_with_order::<false>(data, fields, rotation, translation)
+        }
+    }
+
+    /// Fast path for the usual layout: little-endian FLOAT32 fields packed
+    /// at `offset`. One fixed-size slice per point and `f32` math, as
+    /// tf2_sensor_msgs does with `Eigen::Affine3f`.
+    fn transform_packed_f32(
+        &self,
+        data: &mut [u8],
+        offset: usize,
+        rotation: [[f64; 3]; 3],
+        translation: [f64; 3],
+    ) {
+        let m = rotation.map(|row| row.map(|v| v as f32));
+        let t = translation.map(|v| v as f32);
+        self.for_each_point(data, |point| {
+            let bytes: &mut [u8; 12] = (&mut point[offset..offset + 12])
+                .try_into()
+                .expect("offset was checked against point_step");
+            let x = f32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]);
+            let y = f32::from_le_bytes([bytes[4], bytes[5], bytes[6], bytes[7]]);
+            let z = f32::from_le_bytes([bytes[8], bytes[9], bytes[10], bytes[11]]);
+            bytes[0..4]
+                .copy_from_slice(&(m[0][0] * x + m[0][1] * y + m[0][2] * z + t[0]).to_le_bytes());
+            bytes[4..8]
+                .copy_from_slice(&(m[1][0] * x + m[1][1] * y + m[1][2] * z + t[1]).to_le_bytes());
+            bytes[8..12]
+                .copy_from_slice(&(m[2][0] * x + m[2][1] * y + m[2][2] * z + t[2]).to_le_bytes());
+        });
+    }
+
+    fn transform_with_order<const BE: bool>(
+        &self,
+        data: &mut [u8],
+        fields: VectorFields,
+        rotation: [[f64; 3]; 3],
+        translation: [f64; 3],
+    ) {
+        let offsets = fields.offsets;
+        match fields.datatype {
+            PF_INT8 => self.transform_as::<i8, BE>(data, offsets, rotation, translation),
+            PF_UINT8 => self.transform_as::<u8, BE>(data, offsets, rotation, translation),
+            PF_INT16 => self.transform_as::<i16, BE>(data, offsets, rotation, translation),
+            PF_UINT16 => self.transform_as::<u16, BE>(data, offsets, rotation, translation),
+            PF_INT32 => self.transform_as::<i32, BE>(data, offsets, rotation, translation),
+            PF_UINT32 => self.transform_as::<u32, BE>(data, offsets, rotation, translation),
+            PF_FLOAT32 => self.transform_as::<f32, BE>(data, offsets, rotation, translation),
+            PF_FLOAT64 => self.transform_as::<f64, BE>(data, offsets, rotation, translation),
+            other => unreachable!("datatype {other} was checked by vector_fields"),
+        }
+    }
+
+    fn transform_as<E: Element, const BE: bool>(
+        &self,
+        data: &mut [u8],
+        offsets: [usize; 3],
+        rotation: [[f64; 3]; 3],
+        translation: [f64; 3],
+    ) {
+        self.for_each_point(data, |point| {
+            let v = offsets.map(|o| E::read::<BE>(&point[o..o + E::SIZE]));
+            for ((o, row), t) in offsets.into_iter().zip(rotation).zip(translation) {
+                let out = row[0] * v[0] + row[1] * v[1] + row[2] * v[2] + t;
+                E::write::<BE>(&mut point[o..o + E::SIZE], out);
+            }
+        });
+    }
+
+    /// Calls `f` with the bytes of every point, skipping row padding.
+    fn for_each_point(&self, data: &mut [u8], mut f: impl FnMut(&mut [u8])) {
+        if self.width == 0 || self.height == 0 {
+            return;
+        }
+        let row_len = self.width * self.point_step;
+        let row_step = self.row_step.max(row_len);
+        for row in data.chunks_mut(row_step).take(self.height) {
+            row[..row_len]
+                .chunks_exact_mut(self.point_step)
+                .for_each(&mut f);
         }
     }
 }
 
-impl From<ffi::Tf2PointCloud2> for sensor_msgs::msg::PointCloud2 {
-    fn from(pc: ffi::Tf2PointCloud2) -> Self {
-        let mut out = sensor_msgs::msg::PointCloud2::default();
-        out.header.stamp.sec = pc.header.stamp.sec;
-        out.header.stamp.nanosec = pc.header.stamp.nanosec;
-        out.header.frame_id = pc.header.frame_id;
-
-        out.height = pc.height;
-        out.width = pc.width;
-        out.fields = pc
-            .fields
-            .into_iter()
-            .map(|f| {
-                let mut pf = sensor_msgs::msg::PointField::default();
-                pf.name = f.name;
-                pf.offset = f.offset;
-                pf.datatype = f.datatype;
-                pf.count = f.count;
-                pf
-            })
-            .collect();
+/// A `PointField` datatype, converted through `f64` in a fixed byte order.
+trait Element {
+    const SIZE: usize;
+    fn read<const BE: bool>(bytes: &[u8]) -> f64;
+    fn write<const BE: bool>(bytes: &mut [u8], v: f64);
+}
+
+macro_rules! impl_element {
+    ($($ty:ty => $from_f64:expr),* $(,)?) => {
+        $(
+            impl Element for $ty {
+                const SIZE: usize = std::mem::size_of::<$ty>();
+
+                fn read<const BE: bool>(bytes: &[u8]) -> f64 {
+                    let raw = bytes.try_into().expect("slice has the element size");
+                    let v = if BE { <$ty>::from_be_bytes(raw) } else { <$ty>::from_le_bytes(raw) };
+                    v as f64
+                }
+
+                fn write<const BE: bool>(bytes: &mut [u8], v: f64) {
+                    let v: $ty = $from_f64(v);
+                    bytes.copy_from_slice(&if BE { v.to_be_bytes() } else { v.to_le_bytes() });
+                }
+            }
+        )*
+    };
+}
+
+// `as` saturates, so integer fields clamp instead of wrapping.
+impl_element!(
+    i8 => |v: f64| v.round() as i8,
+    u8 => |v: f64| v.round() as u8,
+    i16 => |v: f64| v.round() as i16,
+    u16 => |v: f64| v.round() as u16,
+    i32 => |v: f64| v.round() as i32,
+    u32 => |v: f64| v.round() as u32,
+    f32 => |v: f64| v as f32,
+    f64 => |v: f64| v,
+);
+
+fn datatype_size(datatype: u8) -> Option<usize> {
+    match datatype {
+        PF_INT8 | PF_UINT8 => Some(1),
+        PF_INT16 | PF_UINT16 => Some(2),
+        PF_INT32 | PF_UINT32 | PF_FLOAT32 => Some(4),
+        PF_FLOAT64 => Some(8),
+        _ => None,
+    }
+}
 
-        out.is_bigendian = pc.is_bigendian;
-        out.point_step = pc.point_step;
-        out.row_step = pc.row_step;
-        out.data = pc.data;
-        out.is_dense = pc.is_dense;
-        out
+/// Fields of a three-field vector: `None` when none of `names` exist, an
+/// error when only some do or they cannot be read as one vector.
+fn vector_fields(
+    cloud: &sensor_msgs::msg::PointCloud2,
+    names: [&str; 3],
+) -> Result<Option<VectorFields>, Tf2Error> {
+    let fields = names.map(|name| cloud.fields.iter().find(|f| f.name == name));
+    if fields.iter().all(Option::is_none) {
+        return Ok(None);
     }
+
+    let mut offsets = [0; 3];
+    let mut datatype = None;
+    for ((offset, field), name) in offsets.iter_mut().zip(fields).zip(names) {
+        let field = field
+            .ok_or_else(|| invalid(format!("PointCloud2 has {names:?} fields but no {name}")))?;
+        let size = datatype_size(field.datatype).ok_or_else(|| {
+            invalid(format!(
+                "field {name} has unknown datatype {}",
+                field.datatype
+            ))
+        })?;
+        if *datatype.get_or_insert(field.datatype) != field.datatype {
+            return Err(invalid(format!("fields {names:?} mix datatypes")));
+        }
+        if field.count > 1 {
+            return Err(invalid(format!(
+                "field {name} has count {}, only scalars can be transformed",
+                field.count
+            )));
+        }
+        if field.offset as usize + size > cloud.point_step as usize {
+            return Err(invalid(format!(
+                "field {name} at offset {} does not fit in point_step {}",
+                field.offset, cloud.point_step
+            )));
+        }
+        *offset = field.offset as usize;
+    }
+    Ok(datatype.map(|datatype| VectorFields { offsets, datatype }))
+}
+
+fn invalid(message: String) -> Tf2Error {
+    Tf2Error::InvalidArgument(message)
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::ffi::ffi;
-
-    // ROS PointField datatype enum: FLOAT32 == 7.
-    const PF_FLOAT32: u8 = 7;
 
     fn make_tf(
         parent_frame: &str, // target frame (tf header.frame_id)
@@ -91,25 +320,23 @@ mod tests {
         rotation_xyzw: [f64; 4],
         stamp: (i32, u32),
     ) -> crate::TransformStamped {
-        let raw = ffi::Tf2TransformStamped {
-            stamp: ffi::Tf2Time {
-                sec: stamp.0,
-                nanosec: stamp.1,
-            },
+        crate::TransformStamped {
+            stamp_sec: stamp.0,
+            stamp_nanosec: stamp.1,
             parent_frame: parent_frame.to_string(),
             child_frame: child_frame.to_string(),
+            translation,
+            rotation: rotation_xyzw,
+        }
+    }
 
-            translation_x: translation[0],
-            translation_y: translation[1],
-            translation_z: translation[2],
-
-            rotation_x: rotation_xyzw[0],
-            rotation_y: rotation_xyzw[1],
-            rotation_z: rotation_xyzw[2],
-            rotation_w: rotation_xyzw[3],
-        };
-
-        crate::TransformStamped::from_ffi(raw)
+    fn make_field(name: &str, offset: u32) -> sensor_msgs::msg::PointField {
+        sensor_msgs::msg::PointField {
+            name: name.to_string(),
+            offset,
+            datatype: PF_FLOAT32,
+            count: 1,
+        }
     }
 
     fn make_xyz_cloud(frame: &str, points: &[[f32; 3]]) -> sensor_msgs::msg::PointCloud2 {
@@ -124,25 +351,7 @@ mod tests {
         cloud.is_bigendian = false;
         cloud.is_dense = true;
 
-        let mut x = sensor_msgs::msg::PointField::default();
-        x.name = "x".to_string();
-        x.offset = 0;
-        x.datatype = PF_FLOAT32;
-        x.count = 1;
-
-        let mut y = sensor_msgs::msg::PointField::default();
-        y.name = "y".to_string();
-        y.offset = 4;
-        y.datatype = PF_FLOAT32;
-        y.count = 1;
-
-        let mut z = sensor_msgs::msg::PointField::default();
-        z.name = "z".to_string();
-        z.offset = 8;
-        z.datatype = PF_FLOAT32;
-        z.count = 1;
-
-        cloud.fields = vec![x, y, z];
+        cloud.fields = vec![make_field("x", 0), make_field("y", 4), make_field("z", 8)];
 
         cloud.point_step = 12;
         cloud.row_step = cloud.point_step * cloud.width;
@@ -256,4 +465,386 @@ mod tests {
             "expected an error for malformed PointCloud2, got: {res:?}"
         );
     }
+
+    /// A cloud with x/y/z, intensity and normals, one padding byte per row
+    /// and two rows, to exercise offsets and `row_step`.
+    fn make_rich_cloud() -> sensor_msgs::msg::PointCloud2 {
+        let mut cloud = sensor_msgs::msg::PointCloud2::default();
+        cloud.header.frame_id = "lidar".to_string();
+        cloud.height = 2;
+        cloud.width = 3;
+        cloud.fields = vec![
+            make_field("intensity", 0),
+            make_field("x", 4),
+            make_field("y", 8),
+            make_field("z", 12),
+            make_field("normal_x", 16),
+            make_field("normal_y", 20),
+            make_field("normal_z", 24),
+        ];
+        cloud.point_step = 28;
+        cloud.row_step = cloud.point_step * cloud.width + 1;
+        for i in 0..cloud.height * cloud.width {
+            let v = i as f32;
+            for f in [100.0 + v, v, -v, 0.5 * v, 1.0, 0.0, 0.0] {
+                cloud.data.extend_from_slice(&f.to_le_bytes());
+            }
+            if i % cloud.width == cloud.width - 1 {
+                cloud.data.push(0xAB);
+            }
+        }
+        cloud
+    }
+
+    fn read_f32(
+        cloud: &sensor_msgs::msg::PointCloud2,
+        row: usize,
+        col: usize,
+        offset: usize,
+    ) -> f32 {
+        let at = row * cloud.row_step as usize + col * cloud.point_step as usize + offset;
+        f32::from_le_bytes(cloud.data[at..at + 4].try_into().unwrap())
+    }
+
+    #[test]
+    fn in_place_rotates_normals_and_keeps_other_bytes() {
+        let s = (0.5f64).sqrt();
+        let tf = make_tf("map", "lidar", [1.0, 2.0, 3.0], [0.0, 0.0, s, s], (7, 0));
+
+        let mut cloud = make_rich_cloud();
+        let copied = cloud.apply_transform(&tf).unwrap();
+        cloud.apply_transform_in_place(&tf).unwrap();
+        assert_eq!(cloud.data, copied.data);
+        assert_eq!(cloud.header.frame_id, "map");
+        assert_eq!(cloud.header.stamp.sec, 7);
+
+        for (row, col) in [(0, 0), (0, 2), (1, 1)] {
+            let v = (row * 3 + col) as f32;
+            assert_near(read_f32(&cloud, row, col, 0), 100.0 + v, 0.0, "intensity");
+            // (v, -v, v/2) yawed by +90 deg -> (v, v, v/2), then translated.
+            assert_near(read_f32(&cloud, row, col, 4), 1.0 + v, 1e-5, "x");
+            assert_near(read_f32(&cloud, row, col, 8), 2.0 + v, 1e-5, "y");
+            assert_near(read_f32(&cloud, row, col, 12), 3.0 + 0.5 * v, 1e-5, "z");
+            // Normal (1, 0, 0) is only rotated.
+            assert_near(read_f32(&cloud, row, col, 16), 0.0, 1e-6, "normal_x");
+            assert_near(read_f32(&cloud, row, col, 20), 1.0, 1e-6, "normal_y");
+            assert_near(read_f32(&cloud, row, col, 24), 0.0, 1e-6, "normal_z");
+        }
+        assert_eq!(cloud.data[cloud.row_step as usize - 1], 0xAB);
+        assert_eq!(*cloud.data.last().unwrap(), 0xAB);
+    }
+
+    #[test]
+    fn rejects_layouts_it_cannot_read() {
+        let tf = make_tf("map", "lidar", [0.0; 3], [0.0, 0.0, 0.0, 1.0], (0, 0));
+        let expect_invalid = |cloud: sensor_msgs::msg::PointCloud2| {
+            let res = cloud.apply_transform(&tf);
+            assert!(matches!(res, Err(Tf2Error::InvalidArgument(_))), "{res:?}");
+        };
+
+        let mut cloud = make_rich_cloud();
+        cloud.fields.retain(|f| f.name != "normal_y");
+        expect_invalid(cloud);
+
+        let mut cloud = make_rich_cloud();
+        cloud.fields[1].datatype = PF_UINT32; // next to FLOAT32 y and z
+        expect_invalid(cloud);
+
+        let mut cloud = make_rich_cloud();
+        cloud.fields[1].datatype = 9;
+        expect_invalid(cloud);
+
+        let mut cloud = make_rich_cloud();
+        cloud.fields[5].count = 3;
+        expect_invalid(cloud);
+
+        let mut cloud = make_rich_cloud();
+        cloud.data.truncate(cloud.data.len() - 2);
+        expect_invalid(cloud);
+
+        let mut cloud = make_rich_cloud();
+        cloud.fields[3].offset = 26;
+        expect_invalid(cloud);
+
+        let mut cloud = make_rich_cloud();
+        cloud.width = u32::MAX;
+        cloud.height = u32::MAX;
+        cloud.point_step = u32::MAX;
+        cloud.row_step = u32::MAX;
+        expect_invalid(cloud);
+    }
+
+    fn encode(datatype: u8, big_endian: bool, v: f64) -> Vec<u8> {
+        macro_rules! bytes {
+            ($v:expr) => {
+                if big_endian {
+                    $v.to_be_bytes().to_vec()
+                } else {
+                    $v.to_le_bytes().to_vec()
+                }
+            };
+        }
+        match datatype {
+            PF_INT8 => bytes!(v as i8),
+            PF_UINT8 => bytes!(v as u8),
+            PF_INT16 => bytes!(v as i16),
+            PF_UINT16 => bytes!(v as u16),
+            PF_INT32 => bytes!(v as i32),
+            PF_UINT32 => bytes!(v as u32),
+            PF_FLOAT32 => bytes!(v as f32),
+            PF_FLOAT64 => bytes!(v),
+            other => panic!("no test encoding for datatype {other}"),
+        }
+    }
+
+    /// Single-row cloud with `fields` packed back to back, one value per
+    /// field and point.
+    fn make_typed_cloud(
+        fields: &[(&str, u8)],
+        big_endian: bool,
+        points: &[&[f64]],
+    ) -> sensor_msgs::msg::PointCloud2 {
+        let mut cloud = sensor_msgs::msg::PointCloud2::default();
+        cloud.header.frame_id = "lidar".to_string();
+        cloud.height = 1;
+        cloud.width = points.len() as u32;
+        cloud.is_bigendian = big_endian;
+        let mut offset = 0;
+        for &(name, datatype) in fields {
+            let mut field = make_field(name, offset);
+            field.datatype = datatype;
+            offset += datatype_size(datatype).unwrap() as u32;
+            cloud.fields.push(field);
+        }
+        cloud.point_step = offset;
+        cloud.row_step = offset * cloud.width;
+        for point in points {
+            for (&(_, datatype), &v) in fields.iter().zip(point.iter()) {
+                cloud.data.extend(encode(datatype, big_endian, v));
+            }
+        }
+        cloud
+    }
+
+    fn read_typed(cloud: &sensor_msgs::msg::PointCloud2, index: usize, name: &str) -> f64 {
+        let field = cloud.fields.iter().find(|f| f.name == name).unwrap();
+        let at = index * cloud.point_step as usize + field.offset as usize;
+        let size = datatype_size(field.datatype).unwrap();
+        let bytes = &cloud.data[at..at + size];
+        if cloud.is_bigendian {
+            read_typed_as::<true>(field.datatype, bytes)
+        } else {
+            read_typed_as::<false>(field.datatype, bytes)
+        }
+    }
+
+    fn read_typed_as<const BE: bool>(datatype: u8, bytes: &[u8]) -> f64 {
+        match datatype {
+            PF_INT8 => i8::read::<BE>(bytes),
+            PF_INT16 => i16::read::<BE>(bytes),
+            PF_UINT16 => u16::read::<BE>(bytes),
+            PF_FLOAT32 => f32::read::<BE>(bytes),
+            PF_FLOAT64 => f64::read::<BE>(bytes),
+            other => panic!("no test decoding for datatype {other}"),
+        }
+    }
+
+    #[test]
+    fn transforms_big_endian_float64_and_viewpoint() {
+        let s = (0.5f64).sqrt();
+        let tf = make_tf("map", "lidar", [1.0, 2.0, 3.0], [0.0, 0.0, s, s], (0, 0));
+        let fields = [
+            ("x", PF_FLOAT64),
+            ("y", PF_FLOAT64),
+            ("z", PF_FLOAT64),
+            ("vp_x", PF_FLOAT32),
+            ("vp_y", PF_FLOAT32),
+            ("vp_z", PF_FLOAT32),
+        ];
+        let x = 123456.789012345;
+        let mut cloud = make_typed_cloud(&fields, true, &[&[x, 0.0, 0.0, 0.0, -1.0, 0.0]]);
+        cloud.apply_transform_in_place(&tf).unwrap();
+
+        // Only exact in f64: an f32 round trip would lose the low digits.
+        assert!((read_typed(&cloud, 0, "x") - 1.0).abs() < 1e-9);
+        assert!((read_typed(&cloud, 0, "y") - (2.0 + x)).abs() < 1e-9);
+        assert!((read_typed(&cloud, 0, "z") - 3.0).abs() < 1e-9);
+        // The viewpoint is a position: rotated and translated.
+        assert!((read_typed(&cloud, 0, "vp_x") - 2.0).abs() < 1e-6);
+        assert!((read_typed(&cloud, 0, "vp_y") - 2.0).abs() < 1e-6);
+        assert!((read_typed(&cloud, 0, "vp_z") - 3.0).abs() < 1e-6);
+    }
+
+    #[test]
+    fn integer_fields_round_and_saturate() {
+        let s = (0.5f64).sqrt();
+        let tf = make_tf("map", "lidar", [0.4, 0.0, 10.0], [0.0, 0.0, s, s], (0, 0));
+        let fields = [
+            ("x", PF_INT16),
+            ("y", PF_INT16),
+            ("z", PF_INT16),
+            ("normal_x", PF_INT8),
+            ("normal_y", PF_INT8),
+            ("normal_z", PF_INT8),
+        ];
+        let mut cloud =
+            make_typed_cloud(&fields, false, &[&[10.0, -20.0, 32760.0, 100.0, 0.0, 0.0]]);
+        cloud.apply_transform_in_place(&tf).unwrap();
+
+        assert_eq!(read_typed(&cloud, 0, "x"), 20.0); // 20.4
+        assert_eq!(read_typed(&cloud, 0, "y"), 10.0);
+        assert_eq!(read_typed(&cloud, 0, "z"), i16::MAX as f64);
+        // Normals are only rotated.
+        assert_eq!(read_typed(&cloud, 0, "normal_x"), 0.0);
+        assert_eq!(read_typed(&cloud, 0, "normal_y"), 100.0);
+        assert_eq!(read_typed(&cloud, 0, "normal_z"), 0.0);
+    }
+
+    #[test]
+    fn byte_order_does_not_change_the_result() {
+        let tf = TransformStamped::from_euler("map", "lidar", [0.5, -1.0, 2.0], [0.3, 0.2, -1.1]);
+        let fields = [("x", PF_UINT16), ("y", PF_UINT16), ("z", PF_UINT16)];
+        let points: [&[f64]; 2] = [&[1000.0, 2000.0, 300.0], &[5.0, 60000.0, 7.0]];
+
+        let mut little = make_typed_cloud(&fields, false, &points);
+        let mut big = make_typed_cloud(&fields, true, &points);
+        little.apply_transform_in_place(&tf).unwrap();
+        big.apply_transform_in_place(&tf).unwrap();
+        for index in 0..2 {
+            for name in ["x", "y", "z"] {
+                assert_eq!(
+                    read_typed(&little, index, name),
+                    read_typed(&big, index, name)
+                );
+            }
+        }
+        assert_ne!(little.data, big.data);
+    }
+
+    #[test]
+    fn packed_float32_fast_path_matches_generic_codec() {
+        let tf = TransformStamped::from_euler("map", "lidar", [0.3, -12.0, 1.5], [0.1, -0.4, 2.0]);
+        let rotation = math::quat_to_matrix(tf.rotation);
+        let mut fast = make_rich_cloud();
+        let mut generic = make_rich_cloud();
+
+        let layout = CloudLayout::of(&fast).unwrap();
+        layout.transform(&mut fast.data, layout.xyz, rotation, tf.translation);
+        layout.transform_as::<f32, false>(
+            &mut generic.data,
+            layout.xyz.offsets,
+            rotation,
+            tf.translation,
+        );
+
+        for (row, col) in [(0, 0), (0, 2), (1, 1)] {
+            for offset in [4, 8, 12] {
+                let (a, b) = (
+                    read_f32(&fast, row, col, offset),
+                    read_f32(&generic, row, col, offset),
+                );
+                assert_near(a, b, 1e-4, "coordinate");
+            }
+        }
+    }
+
+    #[cfg(feature = "tf2-cpp")]
+    mod tf2_oracle {
+        use super::*;
+        use crate::ffi::ffi;
+        use crate::ffi_utils::call_out;
+
+        fn transform_with_tf2(
+            cloud: &sensor_msgs::msg::PointCloud2,
+            tf: &TransformStamped,
+        ) -> sensor_msgs::msg::PointCloud2 {
+            let ffi_in: ffi::Tf2PointCloud2 = cloud.into();
+            call_out(|out| ffi::do_transform_pointcloud2(&ffi_in, &tf.to_ffi(), out))
+                .expect("tf2 should transform the cloud")
+                .into()
+        }
+
+        #[test]
+        fn matches_tf2_sensor_msgs() {
+            let tf =
+                TransformStamped::from_euler("map", "lidar", [0.3, -12.0, 1.5], [0.1, -0.4, 2.0]);
+            let points: Vec<[f32; 3]> = (0..64)
+                .map(|i| {
+                    let t = i as f32;
+                    [t.sin() * 40.0, t.cos() * 25.0 - 3.0, t * 0.1]
+                })
+                .collect();
+            let cloud = make_xyz_cloud("lidar", &points);
+
+            let ours = read_xyz_cloud(&cloud.apply_transform(&tf).unwrap());
+            let theirs = read_xyz_cloud(&transform_with_tf2(&cloud, &tf));
+            for (a, b) in ours.iter().zip(&theirs) {
+                for k in 0..3 {
+                    assert_near(a[k], b[k], 1e-4, "coordinate");
+                }
+            }
+        }
+
+        impl From<&sensor_msgs::msg::PointCloud2> for ffi::Tf2PointCloud2 {
+            fn from(pc: &sensor_msgs::msg::PointCloud2) -> Self {
+                ffi::Tf2PointCloud2 {
+                    header: ffi::Tf2Header {
+                        stamp: ffi::Tf2Time {
+                            sec: pc.header.stamp.sec,
+                            nanosec: pc.header.stamp.nanosec,
+                        },
+                        frame_id: pc.header.frame_id.clone(),
+                    },
+                    height: pc.height,
+                    width: pc.width,
+                    fields: pc
+                        .fields
+                        .iter()
+                        .map(|f| ffi::Tf2PointField {
+                            name: f.name.clone(),
+                            offset: f.offset,
+                            datatype: f.datatype,
+                            count: f.count,
+                        })
+                        .collect(),
+                    is_bigendian: pc.is_bigendian,
+                    point_step: pc.point_step,
+                    row_step: pc.row_step,
+                    data: pc.data.clone(),
+                    is_dense: pc.is_dense,
+                }
+            }
+        }
+
+        impl From<ffi::Tf2PointCloud2> for sensor_msgs::msg::PointCloud2 {
+            fn from(pc: ffi::Tf2PointCloud2) -> Self {
+                let mut out = sensor_msgs::msg::PointCloud2::default();
+                out.header.stamp.sec = pc.header.stamp.sec;
+                out.header.stamp.nanosec = pc.header.stamp.nanosec;
+                out.header.frame_id = pc.header.frame_id;
+
+                out.height = pc.height;
+                out.width = pc.width;
+                out.fields = pc
+                    .fields
+                    .into_iter()
+                    .map(|f| {
+                        let mut pf = sensor_msgs::msg::PointField::default();
+                        pf.name = f.name;
+                        pf.offset = f.offset;
+                        pf.datatype = f.datatype;
+                        pf.count = f.count;
+                        pf
+                    })
+                    .collect();
+
+                out.is_bigendian = pc.is_bigendian;
+                out.point_step = pc.point_step;
+                out.row_step = pc.row_step;
+                out.data = pc.data;
+                out.is_dense = pc.is_dense;
+                out
+            }
+        }
+    }
 }
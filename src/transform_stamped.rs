@@ -1,4 +1,15 @@
+use crate::error::{InvalidTransform, Tf2Error};
+#[cfg(feature = "tf2-cpp")]
 use crate::ffi::ffi;
+use crate::math;
+use crate::time::LookupTime;
+
+/// How far `|q|^2` may be from one, as in tf2.
+pub(crate) const QUATERNION_NORMALIZATION_TOLERANCE: f64 = 10e-3;
+
+/// How far `|q|` may be from one for
+/// [`normalize_rotation`](TransformStamped::normalize_rotation) to rescale it.
+pub const QUATERNION_RESCALE_TOLERANCE: f64 = 0.1;
 
 #[derive(Clone, Debug)]
 pub struct TransformStamped {
@@ -10,6 +21,204 @@ pub struct TransformStamped {
     pub rotation: [f64; 4], // x,y,z,w
 }
 
+impl TransformStamped {
+    /// Checks for what tf2 rejects (empty or identical frames, a rotation
+    /// that is not a unit quaternion) and for what it lets through or
+    /// silently fixes: non-finite values and leading slashes in frame ids.
+    pub fn validate(&self) -> Result<(), InvalidTransform> {
+        if self.parent_frame.is_empty() {
+            return Err(InvalidTransform::EmptyParentFrame);
+        }
+        if self.child_frame.is_empty() {
+            return Err(InvalidTransform::EmptyChildFrame);
+        }
+        for frame in [&self.parent_frame, &self.child_frame] {
+            if frame.starts_with('/') {
+                return Err(InvalidTransform::LeadingSlash(frame.clone()));
+            }
+        }
+        if self.parent_frame == self.child_frame {
+            return Err(InvalidTransform::SelfTransform(self.child_frame.clone()));
+        }
+        if !self.translation.iter().all(|v| v.is_finite()) {
+            return Err(InvalidTransform::NonFiniteTranslation(self.translation));
+        }
+        if !self.rotation.iter().all(|v| v.is_finite()) {
+            return Err(InvalidTransform::NonFiniteRotation(self.rotation));
+        }
+        let norm = math::quat_norm(self.rotation);
+        if (norm * norm - 1.0).abs() >= QUATERNION_NORMALIZATION_TOLERANCE {
+            return Err(InvalidTransform::NonUnitQuaternion {
+                rotation: self.rotation,
+                norm,
+            });
+        }
+        Ok(())
+    }
+
+    /// `parent_frame <- child_frame` with no offset, stamped zero.
+    pub fn identity(parent_frame: impl Into<String>, child_frame: impl Into<String>) -> Self {
+        Self::from_euler(parent_frame, child_frame, [0.0; 3], [0.0; 3])
+    }
+
+    /// Builds a transform from a translation and fixed-axis roll, pitch and
+    /// yaw in radians (tf2's `setRPY` convention), stamped zero.
+    pub fn from_euler(
+        parent_frame: impl Into<String>,
+        child_frame: impl Into<String>,
+        translation: [f64; 3],
+        rpy: [f64; 3],
+    ) -> Self {
+        Self {
+            stamp_sec: 0,
+            stamp_nanosec: 0,
+            parent_frame: parent_frame.into(),
+            child_frame: child_frame.into(),
+            translation,
+            rotation: math::quat_from_rpy(rpy),
+        }
+    }
+
+    /// Roll, pitch and yaw of `rotation`, with pitch in `[-pi/2, pi/2]`.
+    pub fn to_euler(&self) -> [f64; 3] {
+        math::quat_to_rpy(self.rotation)
+    }
+
+    /// Builds a transform from a row-major homogeneous matrix, stamped zero.
+    /// Only the rotation and translation parts are read.
+    pub fn from_matrix(
+        parent_frame: impl Into<String>,
+        child_frame: impl Into<String>,
+        matrix: [[f64; 4]; 4],
+    ) -> Self {
+        let rotation = std::array::from_fn(|r| std::array::from_fn(|c| matrix[r][c]));
+        Self {
+            stamp_sec: 0,
+            stamp_nanosec: 0,
+            parent_frame: parent_frame.into(),
+            child_frame: child_frame.into(),
+            translation: [matrix[0][3], matrix[1][3], matrix[2][3]],
+            rotation: math::quat_from_matrix(rotation),
+        }
+    }
+
+    /// Row-major homogeneous matrix taking child coordinates to parent ones.
+    pub fn to_matrix(&self) -> [[f64; 4]; 4] {
+        let r = math::quat_to_matrix(self.rotation);
+        let t = self.translation;
+        [
+            [r[0][0], r[0][1], r[0][2], t[0]],
+            [r[1][0], r[1][1], r[1][2], t[1]],
+            [r[2][0], r[2][1], r[2][2], t[2]],
+            [0.0, 0.0, 0.0, 1.0],
+        ]
+    }
+
+    /// Expresses a point given in `child_frame` in `parent_frame`.
+    pub fn transform_point(&self, point: [f64; 3]) -> [f64; 3] {
+        math::add(math::quat_rotate(self.rotation, point), self.translation)
+    }
+
+    /// Like [`transform_point`](Self::transform_point), but only rotates, as
+    /// for directions, velocities or forces.
+    pub fn transform_vector(&self, vector: [f64; 3]) -> [f64; 3] {
+        math::quat_rotate(self.rotation, vector)
+    }
+
+    /// `child_frame <- parent_frame`, with the same stamp.
+    pub fn inverse(&self) -> Self {
+        let rotation = math::quat_conjugate(self.rotation);
+        Self {
+            stamp_sec: self.stamp_sec,
+            stamp_nanosec: self.stamp_nanosec,
+            parent_frame: self.child_frame.clone(),
+            child_frame: self.parent_frame.clone(),
+            translation: math::quat_rotate(rotation, math::scale(self.translation, -1.0)),
+            rotation,
+        }
+    }
+
+    /// Chains `a <- b` (self) with `b <- c` (`other`) into `a <- c`. Fails if
+    /// `other.parent_frame` is not `self.child_frame`.
+    ///
+    /// The result is stamped with the older of the two stamps, ignoring zero
+    /// (static) ones, like tf2 resolving a chain to its latest common time.
+    pub fn compose(&self, other: &TransformStamped) -> Result<TransformStamped, Tf2Error> {
+        if self.child_frame != other.parent_frame {
+            return Err(Tf2Error::InvalidArgument(format!(
+                "cannot compose {} <- {} with {} <- {}: frames do not chain",
+                self.parent_frame, self.child_frame, other.parent_frame, other.child_frame
+            )));
+        }
+        let stamp = match (self.stamp_nanos(), other.stamp_nanos()) {
+            (0, ns) | (ns, 0) => ns,
+            (a, b) => a.min(b),
+        };
+        Ok(TransformStamped {
+            parent_frame: self.parent_frame.clone(),
+            child_frame: other.child_frame.clone(),
+            translation: self.transform_point(other.translation),
+            rotation: math::quat_mul(self.rotation, other.rotation),
+            ..Self::stamped(stamp)
+        })
+    }
+
+    /// Interpolates towards `other` (same frames), `t = 0` giving `self`:
+    /// linearly for the translation and the stamp, by slerp for the rotation.
+    pub fn interpolate(
+        &self,
+        other: &TransformStamped,
+        t: f64,
+    ) -> Result<TransformStamped, Tf2Error> {
+        if self.parent_frame != other.parent_frame || self.child_frame != other.child_frame {
+            return Err(Tf2Error::InvalidArgument(format!(
+                "cannot interpolate {} <- {} towards {} <- {}: frames differ",
+                self.parent_frame, self.child_frame, other.parent_frame, other.child_frame
+            )));
+        }
+        let (a, b) = (self.stamp_nanos(), other.stamp_nanos());
+        let stamp = a + ((b - a) as f64 * t).round() as i64;
+        Ok(TransformStamped {
+            parent_frame: self.parent_frame.clone(),
+            child_frame: self.child_frame.clone(),
+            translation: math::lerp(self.translation, other.translation, t),
+            rotation: math::quat_slerp(self.rotation, other.rotation, t),
+            ..Self::stamped(stamp)
+        })
+    }
+
+    fn stamp_nanos(&self) -> i64 {
+        self.stamp_sec as i64 * 1_000_000_000 + self.stamp_nanosec as i64
+    }
+
+    /// Identity with no frames, stamped `stamp_ns`.
+    fn stamped(stamp_ns: i64) -> Self {
+        let (stamp_sec, stamp_nanosec) = match LookupTime::from_nanos(stamp_ns) {
+            LookupTime::Time { sec, nanosec } => (sec, nanosec),
+            LookupTime::Latest => (0, 0),
+        };
+        Self {
+            stamp_sec,
+            stamp_nanosec,
+            parent_frame: String::new(),
+            child_frame: String::new(),
+            translation: [0.0; 3],
+            rotation: math::QUAT_IDENTITY,
+        }
+    }
+
+    /// Rescales `rotation` to unit length if its norm is within
+    /// [`QUATERNION_RESCALE_TOLERANCE`] of one. Anything further off is left
+    /// alone for [`validate`](Self::validate) to reject.
+    pub fn normalize_rotation(&mut self) {
+        let norm = math::quat_norm(self.rotation);
+        if (norm - 1.0).abs() < QUATERNION_RESCALE_TOLERANCE {
+            self.rotation = self.rotation.map(|v| v / norm);
+        }
+    }
+}
+
+#[cfg(feature = "tf2-cpp")]
 impl TransformStamped {
     pub fn to_ffi(&self) -> ffi::Tf2TransformStamped {
         ffi::Tf2TransformStamped {
@@ -87,4 +296,191 @@ impl From<&TransformStamped> for geometry_msgs::msg::TransformStamped {
 
         msg
     }
-}
\ No newline at end of file
+}
+
+impl From<&geometry_msgs::msg::TransformStamped> for TransformStamped {
+    fn from(t: &geometry_msgs::msg::TransformStamped) -> Self {
+        TransformStamped {
+            stamp_sec: t.header.stamp.sec,
+            stamp_nanosec: t.header.stamp.nanosec,
+            parent_frame: t.header.frame_id.clone(),
+            child_frame: t.child_frame_id.clone(),
+            translation: [
+                t.transform.translation.x,
+                t.transform.translation.y,
+                t.transform.translation.z,
+            ],
+            rotation: [
+                t.transform.rotation.x,
+                t.transform.rotation.y,
+                t.transform.rotation.z,
+                t.transform.rotation.w,
+            ],
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::f64::consts::{FRAC_PI_2, FRAC_PI_4};
+
+    fn make_tf(parent_frame: &str, child_frame: &str) -> TransformStamped {
+        TransformStamped {
+            stamp_sec: 1,
+            stamp_nanosec: 0,
+            parent_frame: parent_frame.to_string(),
+            child_frame: child_frame.to_string(),
+            translation: [1.0, 2.0, 3.0],
+            rotation: [0.0, 0.0, 0.0, 1.0],
+        }
+    }
+
+    #[test]
+    fn validate_reports_each_problem() {
+        assert_eq!(make_tf("odom", "base_link").validate(), Ok(()));
+        assert_eq!(
+            make_tf("", "base_link").validate(),
+            Err(InvalidTransform::EmptyParentFrame)
+        );
+        assert_eq!(
+            make_tf("odom", "").validate(),
+            Err(InvalidTransform::EmptyChildFrame)
+        );
+        assert_eq!(
+            make_tf("/odom", "base_link").validate(),
+            Err(InvalidTransform::LeadingSlash("/odom".to_string()))
+        );
+        assert_eq!(
+            make_tf("odom", "odom").validate(),
+            Err(InvalidTransform::SelfTransform("odom".to_string()))
+        );
+
+        let mut tf = make_tf("odom", "base_link");
+        tf.translation[1] = f64::INFINITY;
+        assert!(matches!(
+            tf.validate(),
+            Err(InvalidTransform::NonFiniteTranslation(_))
+        ));
+
+        let mut tf = make_tf("odom", "base_link");
+        tf.rotation[0] = f64::NAN;
+        assert!(matches!(
+            tf.validate(),
+            Err(InvalidTransform::NonFiniteRotation(_))
+        ));
+
+        let mut tf = make_tf("odom", "base_link");
+        tf.rotation = [0.0, 0.0, 0.0, 1.1];
+        assert!(matches!(
+            tf.validate(),
+            Err(InvalidTransform::NonUnitQuaternion { .. })
+        ));
+    }
+
+    fn assert_near<const N: usize>(a: [f64; N], b: [f64; N]) {
+        for i in 0..N {
+            assert!((a[i] - b[i]).abs() < 1e-9, "{a:?} vs {b:?}");
+        }
+    }
+
+    #[test]
+    fn compose_chains_frames_and_inverse_undoes_it() {
+        let a_b = TransformStamped::from_euler("a", "b", [1.0, 0.0, 0.0], [0.0, 0.0, FRAC_PI_2]);
+        let mut b_c = TransformStamped::from_euler("b", "c", [0.0, 2.0, 0.0], [0.3, -0.2, 0.1]);
+        b_c.stamp_sec = 4;
+
+        let a_c = a_b.compose(&b_c).unwrap();
+        assert_eq!(
+            (a_c.parent_frame.as_str(), a_c.child_frame.as_str()),
+            ("a", "c")
+        );
+        assert_eq!((a_c.stamp_sec, a_c.stamp_nanosec), (4, 0));
+        let p = [0.5, -1.0, 2.0];
+        assert_near(
+            a_c.transform_point(p),
+            a_b.transform_point(b_c.transform_point(p)),
+        );
+
+        let back = a_c.compose(&b_c.inverse()).unwrap();
+        assert_eq!(back.child_frame, "b");
+        assert_near(back.translation, a_b.translation);
+        assert_near(back.to_euler(), a_b.to_euler());
+
+        assert!(matches!(
+            b_c.compose(&a_b),
+            Err(Tf2Error::InvalidArgument(_))
+        ));
+    }
+
+    #[test]
+    fn transform_point_and_vector() {
+        let tf =
+            TransformStamped::from_euler("map", "odom", [1.0, 2.0, 3.0], [0.0, 0.0, FRAC_PI_2]);
+        assert_near(tf.transform_point([1.0, 0.0, 0.0]), [1.0, 3.0, 3.0]);
+        assert_near(tf.transform_vector([1.0, 0.0, 0.0]), [0.0, 1.0, 0.0]);
+
+        let identity = TransformStamped::identity("map", "odom");
+        assert_near(identity.transform_point([1.0, 2.0, 3.0]), [1.0, 2.0, 3.0]);
+    }
+
+    #[test]
+    fn euler_and_matrix_round_trip() {
+        let rpy = [0.4, -1.1, 2.9];
+        let tf = TransformStamped::from_euler("map", "odom", [1.0, -2.0, 0.5], rpy);
+        assert_near(tf.to_euler(), rpy);
+
+        let m = tf.to_matrix();
+        let p = [0.3, 0.2, -0.7];
+        let by_matrix: [f64; 3] =
+            std::array::from_fn(|r| (0..3).map(|c| m[r][c] * p[c]).sum::<f64>() + m[r][3]);
+        assert_near(by_matrix, tf.transform_point(p));
+
+        let back = TransformStamped::from_matrix("map", "odom", m);
+        assert_near(back.translation, tf.translation);
+        let dot: f64 = (0..4).map(|i| back.rotation[i] * tf.rotation[i]).sum();
+        assert!((dot.abs() - 1.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn interpolate_blends_pose_and_stamp() {
+        let mut start = TransformStamped::identity("odom", "base_link");
+        start.stamp_sec = 1;
+        let mut end = TransformStamped::from_euler(
+            "odom",
+            "base_link",
+            [2.0, 0.0, 0.0],
+            [0.0, 0.0, FRAC_PI_2],
+        );
+        end.stamp_sec = 2;
+
+        let mid = start.interpolate(&end, 0.5).unwrap();
+        assert_eq!((mid.stamp_sec, mid.stamp_nanosec), (1, 500_000_000));
+        assert_near(mid.translation, [1.0, 0.0, 0.0]);
+        assert_near(mid.to_euler(), [0.0, 0.0, FRAC_PI_4]);
+
+        let other = TransformStamped::identity("map", "base_link");
+        assert!(start.interpolate(&other, 0.5).is_err());
+    }
+
+    #[test]
+    fn normalize_rotation_fixes_nearly_unit_quaternion() {
+        let mut tf = make_tf("odom", "base_link");
+        tf.rotation = [0.0, 0.0, 0.75, 0.75];
+        tf.normalize_rotation();
+        assert!(tf.validate().is_ok());
+        assert!((tf.rotation[2] - std::f64::consts::FRAC_1_SQRT_2).abs() < 1e-12);
+
+        tf.rotation = [0.0, 0.0, 2.0, 2.0];
+        tf.normalize_rotation();
+        assert_eq!(tf.rotation, [0.0, 0.0, 2.0, 2.0]);
+        assert!(matches!(
+            tf.validate(),
+            Err(InvalidTransform::NonUnitQuaternion { .. })
+        ));
+
+        tf.rotation = [0.0; 4];
+        tf.normalize_rotation();
+        assert_eq!(tf.rotation, [0.0; 4]);
+    }
+}
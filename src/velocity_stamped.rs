@@ -0,0 +1,34 @@
+/// Velocity of `body_frame` as returned by
+/// [`BufferCore::lookup_velocity`](crate::BufferCore::lookup_velocity),
+/// expressed in `reference_frame`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct VelocityStamped {
+    pub stamp_sec: i32,
+    pub stamp_nanosec: u32,
+    pub body_frame: String,
+    pub reference_frame: String,
+    /// Linear velocity in m/s.
+    pub linear: [f64; 3],
+    /// Angular velocity in rad/s.
+    pub angular: [f64; 3],
+}
+
+impl From<VelocityStamped> for geometry_msgs::msg::TwistStamped {
+    fn from(v: VelocityStamped) -> Self {
+        let mut msg = geometry_msgs::msg::TwistStamped::default();
+
+        msg.header.stamp.sec = v.stamp_sec;
+        msg.header.stamp.nanosec = v.stamp_nanosec;
+        msg.header.frame_id = v.reference_frame;
+
+        msg.twist.linear.x = v.linear[0];
+        msg.twist.linear.y = v.linear[1];
+        msg.twist.linear.z = v.linear[2];
+
+        msg.twist.angular.x = v.angular[0];
+        msg.twist.angular.y = v.angular[1];
+        msg.twist.angular.z = v.angular[2];
+
+        msg
+    }
+}
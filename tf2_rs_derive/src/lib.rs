@@ -0,0 +1,234 @@
+//! Derive macros for `tf2_rs`, re-exported from it behind the `derive`
+//! feature. See `tf2_rs::derive_support` for what each field kind accepts.
+//!
+//! ```ignore
+//! #[derive(Clone, HasHeader, Transformable)]
+//! struct Detection {
+//!     header: std_msgs::msg::Header,
+//!     #[tf2(pose)]
+//!     pose: geometry_msgs::msg::Pose,
+//!     #[tf2(point)]
+//!     keypoints: Vec<[f64; 3]>,
+//!     #[tf2(stamped)]
+//!     target: geometry_msgs::msg::PointStamped,
+//!     label: String,
+//! }
+//! ```
+
+use proc_macro::TokenStream;
+use proc_macro2::TokenStream as TokenStream2;
+use quote::quote;
+use syn::{Data, DeriveInput, Fields, Index, Member, parse_macro_input};
+
+#[derive(Clone, Copy, PartialEq)]
+enum Kind {
+    Header,
+    Point,
+    Vector,
+    Pose,
+    Payload,
+    Stamped,
+}
+
+struct Field {
+    member: Member,
+    kind: Option<Kind>,
+}
+
+/// Implements `HasHeader` from the `#[tf2(header)]` field, or the field named
+/// `header`. The field needs `frame_id` and `stamp.{sec, nanosec}`, like
+/// `std_msgs::msg::Header`.
+#[proc_macro_derive(HasHeader, attributes(tf2))]
+pub fn derive_has_header(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    expand_has_header(&input)
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into()
+}
+
+/// Implements `Transformable` by transforming every marked field and cloning
+/// the rest. The header takes the frame and stamp of the transform, as in
+/// tf2's `doTransform`. `#[tf2(stamped)]` fields reuse their own
+/// `Transformable` impl; the others use the Rust transform math. Needs
+/// `Clone` and `HasHeader`.
+#[proc_macro_derive(Transformable, attributes(tf2))]
+pub fn derive_transformable(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    expand_transformable(&input)
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into()
+}
+
+/// Implements `TransformablePayload` for header-less structs nested in a
+/// `Transformable` one (mark them `#[tf2(payload)]` there). Needs `Clone`.
+#[proc_macro_derive(TransformablePayload, attributes(tf2))]
+pub fn derive_transformable_payload(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    expand_transformable_payload(&input)
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into()
+}
+
+fn expand_has_header(input: &DeriveInput) -> syn::Result<TokenStream2> {
+    let header = header_field(input, &fields(input)?)?;
+    let name = &input.ident;
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+    Ok(quote! {
+        impl #impl_generics ::tf2_rs::HasHeader for #name #ty_generics #where_clause {
+            fn frame_id(&self) -> &str {
+                &self.#header.frame_id
+            }
+            fn stamp(&self) -> (i32, u32) {
+                (self.#header.stamp.sec, self.#header.stamp.nanosec)
+            }
+        }
+    })
+}
+
+fn expand_transformable(input: &DeriveInput) -> syn::Result<TokenStream2> {
+    let fields = fields(input)?;
+    let header = header_field(input, &fields)?;
+    let transforms = field_transforms(&fields);
+    let name = &input.ident;
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+    Ok(quote! {
+        impl #impl_generics ::tf2_rs::Transformable for #name #ty_generics #where_clause {
+            fn apply_transform(
+                &self,
+                tf: &::tf2_rs::TransformStamped,
+            ) -> ::core::result::Result<Self, ::tf2_rs::Tf2Error> {
+                let mut out = ::core::clone::Clone::clone(self);
+                out.#header.frame_id = ::core::clone::Clone::clone(&tf.parent_frame);
+                out.#header.stamp.sec = tf.stamp_sec;
+                out.#header.stamp.nanosec = tf.stamp_nanosec;
+                #(#transforms)*
+                ::core::result::Result::Ok(out)
+            }
+        }
+    })
+}
+
+fn expand_transformable_payload(input: &DeriveInput) -> syn::Result<TokenStream2> {
+    let fields = fields(input)?;
+    if let Some(field) = fields.iter().find(|f| f.kind == Some(Kind::Header)) {
+        return Err(syn::Error::new_spanned(
+            &field.member,
+            "a payload has no header; derive `HasHeader` and `Transformable` instead",
+        ));
+    }
+    let transforms = field_transforms(&fields);
+    let name = &input.ident;
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+    Ok(quote! {
+        impl #impl_generics ::tf2_rs::TransformablePayload for #name #ty_generics #where_clause {
+            fn transform_payload(
+                &self,
+                tf: &::tf2_rs::TransformStamped,
+            ) -> ::core::result::Result<Self, ::tf2_rs::Tf2Error> {
+                let mut out = ::core::clone::Clone::clone(self);
+                #(#transforms)*
+                ::core::result::Result::Ok(out)
+            }
+        }
+    })
+}
+
+fn field_transforms(fields: &[Field]) -> Vec<TokenStream2> {
+    fields
+        .iter()
+        .filter_map(|field| {
+            let member = &field.member;
+            let call = match field.kind? {
+                Kind::Header => return None,
+                Kind::Point => quote!(::tf2_rs::derive_support::PointField::transform_point),
+                Kind::Vector => quote!(::tf2_rs::derive_support::VectorField::transform_vector),
+                Kind::Pose => quote!(::tf2_rs::derive_support::PoseField::transform_pose),
+                Kind::Payload => quote!(::tf2_rs::TransformablePayload::transform_payload),
+                Kind::Stamped => quote!(::tf2_rs::derive_support::StampedField::transform_stamped),
+            };
+            Some(quote! {
+                out.#member = #call(&self.#member, tf)?;
+            })
+        })
+        .collect()
+}
+
+fn fields(input: &DeriveInput) -> syn::Result<Vec<Field>> {
+    let Data::Struct(data) = &input.data else {
+        return Err(syn::Error::new_spanned(
+            &input.ident,
+            "tf2_rs derives only support structs",
+        ));
+    };
+    let fields = match &data.fields {
+        Fields::Named(fields) => &fields.named,
+        Fields::Unnamed(fields) => &fields.unnamed,
+        Fields::Unit => return Ok(Vec::new()),
+    };
+
+    fields
+        .iter()
+        .enumerate()
+        .map(|(i, field)| {
+            let member = match &field.ident {
+                Some(ident) => Member::Named(ident.clone()),
+                None => Member::Unnamed(Index::from(i)),
+            };
+            let mut kind = None;
+            for attr in field.attrs.iter().filter(|a| a.path().is_ident("tf2")) {
+                attr.parse_nested_meta(|meta| {
+                    let parsed = if meta.path.is_ident("header") {
+                        Kind::Header
+                    } else if meta.path.is_ident("point") {
+                        Kind::Point
+                    } else if meta.path.is_ident("vector") {
+                        Kind::Vector
+                    } else if meta.path.is_ident("pose") {
+                        Kind::Pose
+                    } else if meta.path.is_ident("payload") {
+                        Kind::Payload
+                    } else if meta.path.is_ident("stamped") {
+                        Kind::Stamped
+                    } else {
+                        return Err(meta.error(
+                            "expected one of `header`, `point`, `vector`, `pose`, `payload`, `stamped`",
+                        ));
+                    };
+                    if kind.replace(parsed).is_some() {
+                        return Err(meta.error("a field can only have one tf2 kind"));
+                    }
+                    Ok(())
+                })?;
+            }
+            Ok(Field { member, kind })
+        })
+        .collect()
+}
+
+/// The `#[tf2(header)]` field, else the one named `header`.
+fn header_field(input: &DeriveInput, fields: &[Field]) -> syn::Result<Member> {
+    let marked: Vec<&Field> = fields
+        .iter()
+        .filter(|f| f.kind == Some(Kind::Header))
+        .collect();
+    match marked.as_slice() {
+        [field] => return Ok(field.member.clone()),
+        [_, second, ..] => {
+            return Err(syn::Error::new_spanned(
+                &second.member,
+                "only one field can be `#[tf2(header)]`",
+            ));
+        }
+        [] => {}
+    }
+    fields
+        .iter()
+        .find(|f| matches!(&f.member, Member::Named(ident) if ident == "header"))
+        .map(|f| f.member.clone())
+        .ok_or_else(|| {
+            syn::Error::new_spanned(
+                &input.ident,
+                "no header field: mark one with `#[tf2(header)]` or name it `header`",
+            )
+        })
+}